@@ -1,240 +1,350 @@
 use bracket_lib::prelude::*;
+use snake_common::ai::{self, AStarController, Controller};
+use snake_common::{
+    DeathCause, Direction, FoodKind, Game, GameConfig, HighScores, Hud, MainMenu, Menu, MenuAction,
+    MenuEvent, Replay, ReplayPlayer, Settings, Snake, SnakeId, SpawnPolicy, StepEvent, Theme,
+};
+
+add_wasm_support!();
 
 const SCREEN_WIDTH: i32 = 80;
 const SCREEN_HEIGHT: i32 = 50;
-const FRAME_DURATION: f32 = 75.0;
 const SNAKE_INITIAL_BODY_SIZE: i32 = 5;
+/// Console the HUD is drawn on, layered over the playfield on console 0.
+const HUD_CONSOLE: usize = 1;
+const LAST_RUN_REPLAY: &str = "ascii_snake_last.replay";
+
+const GAME_CONFIG: GameConfig = GameConfig {
+    width: SCREEN_WIDTH,
+    height: SCREEN_HEIGHT,
+    initial_length: SNAKE_INITIAL_BODY_SIZE + 1,
+    opponent: false,
+    power_ups: false,
+    spawn: SpawnPolicy::CLASSIC,
+};
 
 enum GameMode {
     Menu,
     Playing,
     End,
+    Replay,
 }
 
-enum Direction {
-    Up,
-    Down,
-    Right,
-    Left,
-}
-
-struct Food {
-    x: i32,
-    y: i32,
+#[derive(Clone, Copy, PartialEq)]
+enum PlayMode {
+    Solo,
+    VersusCpu,
+    /// The autopilot plays on its own until a key is pressed.
+    Demo,
 }
 
-fn get_initial_body_cells_from_size(head_pos_x: i32, head_pos_y: i32, size: i32) -> Vec<(i32, i32)> {
-    let mut body_cells = Vec::new();
-    for i in 0..size {
-        body_cells.push((head_pos_x - (i + 1), head_pos_y))
-    }
-
-    body_cells
+#[derive(Clone, Copy)]
+enum EndChoice {
+    Retry,
+    Menu,
 }
 
-impl Food {
-    fn new() -> Self {
-        let mut random = RandomNumberGenerator::new();
-        Food {
-            x: random.range(0, SCREEN_WIDTH),
-            y: random.range(0, SCREEN_HEIGHT),
+fn render_food(ctx: &mut BTerm, game: &Game, settings: &Settings) {
+    let theme = settings.theme;
+    for food in &game.foods {
+        if !settings.reduced_motion && food.is_expiring(game.ticks) && game.ticks % 2 == 1 {
+            continue;
         }
-    }
-
-    fn is_snake_collision(&mut self, snake: &Snake) -> bool {
-        self.x == snake.head_pos_x && self.y == snake.head_pos_y
-    }
-
-    fn render(&mut self, ctx: &mut BTerm) {
-        ctx.set(self.x, self.y, RED, BLACK, to_cp437('♥'))
+        let (glyph, color) = match food.kind {
+            FoodKind::Normal => ('♥', theme.food_color()),
+            FoodKind::Golden => ('♦', GOLD),
+            FoodKind::SlowDown => ('▼', LIGHT_BLUE),
+            FoodKind::SpeedUp => ('▲', ORANGE_RED),
+            FoodKind::Shrink => ('○', LIGHT_GREY),
+            FoodKind::Ghost => ('☺', VIOLET),
+        };
+        ctx.set(food.cell.x, food.cell.y, color, BLACK, to_cp437(glyph))
     }
 }
 
-struct Snake {
-    head_pos_x: i32,
-    head_pos_y: i32,
-    direction_unit_vector: (f32, f32),
-    speed: f32,
-    body_cells: Vec<(i32, i32)>,
-    ghost_tail: (i32, i32),
+fn render_snake(ctx: &mut BTerm, snake: &Snake, color: (u8, u8, u8)) {
+    let head = snake.head();
+    ctx.set(head.x, head.y, color, BLACK, to_cp437('@'));
+    snake.body[1..]
+        .iter()
+        .for_each(|body_cell| ctx.set(body_cell.x, body_cell.y, color, BLACK, to_cp437('■')))
 }
 
-impl Snake {
-    fn new(head_pos_x: i32, head_pos_y: i32) -> Self {
-        Snake {
-            head_pos_x,
-            head_pos_y,
-            direction_unit_vector: (1.0, 0.0),
-            speed: 1.0,
-            body_cells: get_initial_body_cells_from_size(head_pos_x, head_pos_y, SNAKE_INITIAL_BODY_SIZE),
-
-            // last position of tail cell
-            ghost_tail: (head_pos_x - 5, head_pos_y),
-        }
-    }
-
-    fn change_direction(&mut self, direction: Direction) {
-        self.direction_unit_vector = match direction {
-            Direction::Up => (0.0, -1.0),
-            Direction::Down => (0.0, 1.0),
-            Direction::Right => (1.0, 0.0),
-            Direction::Left => (-1.0, 0.0),
-        };
-    }
-
-    fn is_wall_collision(&mut self) -> bool {
-        self.head_pos_x < 0
-            || self.head_pos_x > SCREEN_WIDTH
-            || self.head_pos_y < 0
-            || self.head_pos_y > SCREEN_HEIGHT
-    }
-
-    fn is_self_collision(&mut self) -> bool {
-        match self
-            .body_cells
-            .iter()
-            .find(|body_cell| body_cell.0 == self.head_pos_x && body_cell.1 == self.head_pos_y)
-        {
-            Some(_) => true,
-            None => false,
-        }
-    }
-
-    fn slither(&mut self) {
-        if let Some(last) = self.body_cells.last() {
-            self.ghost_tail = (last.0, last.1);
-        }
-
-        for i in (0..self.body_cells.len()).rev() {
-            if i != 0 {
-                self.body_cells[i].0 = self.body_cells[i - 1].0;
-                self.body_cells[i].1 = self.body_cells[i - 1].1;
-            } else {
-                self.body_cells[0].0 = self.head_pos_x;
-                self.body_cells[0].1 = self.head_pos_y;
-            }
-        }
-
-        self.head_pos_x += (self.direction_unit_vector.0 * self.speed) as i32;
-        self.head_pos_y += (self.direction_unit_vector.1 * self.speed) as i32;
-    }
-
-    fn grow(&mut self) {
-        self.body_cells.push((self.ghost_tail.0, self.ghost_tail.1));
-    }
-
-    fn render(&mut self, ctx: &mut BTerm) {
-        ctx.set(
-            self.head_pos_x,
-            self.head_pos_y,
-            YELLOW,
-            BLACK,
-            to_cp437('@'),
-        );
-        self.body_cells
-            .iter()
-            .for_each(|body_cell| ctx.set(body_cell.0, body_cell.1, YELLOW, BLACK, to_cp437('■')))
+fn render_board(ctx: &mut BTerm, game: &Game, settings: &Settings) {
+    let theme = settings.theme;
+    render_snake(ctx, &game.snake, theme.snake_color());
+    if let Some(opponent) = &game.opponent {
+        render_snake(ctx, opponent, theme.opponent_color());
     }
+    render_food(ctx, game, settings);
 }
 
 struct State {
     game_mode: GameMode,
-    snake: Snake,
-    food: Food,
+    menu: MainMenu,
+    game: Game,
+    play_mode: PlayMode,
+    replay: Replay,
+    replay_player: Option<ReplayPlayer>,
+    next_direction: Option<Direction>,
     frame_time: f32,
-    score: i32,
+    time_alive_ms: f32,
+    end_menu: Menu<EndChoice>,
+    hud: Hud,
 }
 
 impl State {
     fn new() -> Self {
         Self {
             game_mode: GameMode::Menu,
-            snake: Snake::new(15, 25),
-            food: Food::new(),
+            menu: MainMenu::new(
+                "Welcome to this unknown game",
+                HighScores::load("ascii_snake_scores.txt"),
+            ),
+            game: Game::new(GAME_CONFIG, 0),
+            play_mode: PlayMode::Solo,
+            replay: Replay::new(GAME_CONFIG, 0, 0.0),
+            replay_player: None,
+            next_direction: None,
             frame_time: 0.0,
-            score: 0,
+            time_alive_ms: 0.0,
+            end_menu: end_menu(),
+            hud: Hud::new(),
         }
     }
 
     fn main_menu(&mut self, ctx: &mut BTerm) {
-        ctx.cls();
-        ctx.print_centered(5, "Welcome to this unknown game");
-        ctx.print_centered(8, "(P) Play Game");
-        ctx.print_centered(9, "(Q) Quit Game");
-
-        if let Some(key) = ctx.key {
-            match key {
-                VirtualKeyCode::P => self.restart(),
-                VirtualKeyCode::Q => ctx.quitting = true,
-                _ => {}
-            }
+        match self.menu.tick(ctx) {
+            Some(MenuAction::Play) => self.start(PlayMode::Solo),
+            Some(MenuAction::PlayVersusCpu) => self.start(PlayMode::VersusCpu),
+            Some(MenuAction::Demo) => self.start(PlayMode::Demo),
+            Some(MenuAction::WatchReplay) => match Replay::load(LAST_RUN_REPLAY) {
+                Ok(replay) => self.watch(replay),
+                Err(err) => self
+                    .menu
+                    .report(format!("Can't watch the last run: {}", err)),
+            },
+            Some(MenuAction::Quit) => ctx.quitting = true,
+            None => {}
         }
     }
 
-    fn restart(&mut self) {
+    fn start(&mut self, play_mode: PlayMode) {
+        let seed = RandomNumberGenerator::new().next_u64();
+        let frame_duration = self.menu.settings.difficulty.frame_duration();
+        let config = GameConfig {
+            opponent: play_mode == PlayMode::VersusCpu,
+            power_ups: self.menu.settings.power_ups,
+            spawn: self.menu.settings.food_mode.spawn_policy(),
+            ..GAME_CONFIG
+        };
+
         self.game_mode = GameMode::Playing;
+        self.play_mode = play_mode;
         self.frame_time = 0.0;
-        self.snake = Snake::new(15, 25)
+        self.time_alive_ms = 0.0;
+        self.next_direction = None;
+        self.game = Game::new(config, seed);
+        self.replay = Replay::new(config, seed, frame_duration);
+    }
+
+    fn restart(&mut self) {
+        self.start(self.play_mode)
+    }
+
+    fn watch(&mut self, replay: Replay) {
+        self.replay_player = Some(ReplayPlayer::new(replay));
+        self.game_mode = GameMode::Replay;
+    }
+
+    fn game_over(&mut self) {
+        if self.play_mode == PlayMode::Demo {
+            self.restart();
+            return;
+        }
+
+        self.menu.high_scores.submit(self.game.score);
+        self.replay.finish(self.game.ticks);
+        self.replay.save(LAST_RUN_REPLAY);
+        self.end_menu = end_menu();
+        self.game_mode = GameMode::End;
     }
 
     fn play(&mut self, ctx: &mut BTerm) {
         ctx.cls_bg(BLACK);
 
-        if let Some(key) = ctx.key {
+        let settings = self.menu.settings;
+
+        if self.play_mode == PlayMode::Demo && ctx.key.is_some() {
+            self.game_mode = GameMode::Menu;
+            return;
+        }
+
+        if self.hud.handle_key(ctx.key) {
+            // the key only toggled part of the HUD
+        } else if let Some(key) = ctx.key {
             match key {
-                VirtualKeyCode::W => self.snake.change_direction(Direction::Up),
-                VirtualKeyCode::S => self.snake.change_direction(Direction::Down),
-                VirtualKeyCode::D => self.snake.change_direction(Direction::Right),
-                VirtualKeyCode::A => self.snake.change_direction(Direction::Left),
-                VirtualKeyCode::Q => self.game_mode = GameMode::End,
-                _ => {}
+                VirtualKeyCode::Q => {
+                    self.game.resign();
+                    self.game_over();
+                    return;
+                }
+                key => {
+                    if let Some(direction) = settings.controls.direction(key) {
+                        self.next_direction = Some(direction)
+                    }
+                }
             }
         }
 
         self.frame_time += ctx.frame_time_ms;
+        self.time_alive_ms += ctx.frame_time_ms;
 
-        if self.frame_time > FRAME_DURATION {
+        if self.frame_time > settings.difficulty.frame_duration_for(&self.game) {
             self.frame_time = 0.0;
 
-            self.snake.slither();
+            let input = match self.play_mode {
+                PlayMode::Demo => Some(AStarController.next_direction(&self.game, SnakeId::Player)),
+                _ => self.next_direction.take(),
+            };
+            let opponent_input = ai::opponent_move(&self.game);
+            self.replay.record(self.game.ticks, input);
+            if let StepEvent::Died(_) = self.game.step_versus(input, opponent_input) {
+                self.game_over();
+            }
         }
 
-        self.snake.render(ctx);
+        render_board(ctx, &self.game, &settings);
 
-        self.food.render(ctx);
+        if self.play_mode == PlayMode::Demo {
+            ctx.print_color_centered(SCREEN_HEIGHT - 1, GREY, BLACK, "DEMO - press any key");
+        }
+
+        ctx.set_active_console(HUD_CONSOLE);
+        self.hud.render(
+            ctx,
+            0,
+            &self.game,
+            self.menu.high_scores.best(),
+            self.time_alive_ms,
+        );
+        ctx.set_active_console(0);
+    }
+
+    fn dead(&mut self, ctx: &mut BTerm) {
+        // the board stays frozen as it was at the moment of death
+        ctx.cls_bg(BLACK);
+        render_board(ctx, &self.game, &self.menu.settings);
 
-        if self.food.is_snake_collision(&self.snake) {
-            self.score += 1;
-            self.food = Food::new();
-            self.snake.grow();
+        let death = self.game.death.expect("the game is over");
+        if death.cause != DeathCause::GaveUp {
+            let x = death.cell.x.clamp(0, SCREEN_WIDTH - 1);
+            let y = death.cell.y.clamp(0, SCREEN_HEIGHT - 1);
+            ctx.set(x, y, WHITE, RED, to_cp437('X'));
         }
 
-        if self.snake.is_wall_collision() || self.snake.is_self_collision() {
-            self.game_mode = GameMode::End;
+        ctx.draw_box(20, 4, 40, 18, WHITE, BLACK);
+        ctx.print_color_centered(6, RED, BLACK, "GAME OVER");
+        ctx.print_centered(7, death.cause.describe());
+
+        let seconds = (self.time_alive_ms / 1000.0) as i32;
+        ctx.print(24, 9, format!("Score:       {}", self.game.score));
+        ctx.print(
+            24,
+            10,
+            format!("High score:  {}", self.menu.high_scores.best()),
+        );
+        ctx.print(24, 11, format!("Length:      {}", self.game.snake.length()));
+        ctx.print(24, 12, format!("Foods eaten: {}", self.game.foods_eaten));
+        ctx.print(
+            24,
+            13,
+            format!("Time alive:  {}:{:02}", seconds / 60, seconds % 60),
+        );
+        if self.game.config.opponent {
+            ctx.print(24, 14, format!("CPU score:   {}", self.game.opponent_score));
         }
 
-        ctx.print(0, 0, &format!("Score: {}", self.score));
+        match self.end_menu.handle_key(ctx.key) {
+            MenuEvent::Selected(EndChoice::Retry) => self.restart(),
+            MenuEvent::Selected(EndChoice::Menu) | MenuEvent::Back => {
+                self.game_mode = GameMode::Menu
+            }
+            MenuEvent::None => {}
+        }
+
+        self.end_menu.render(ctx, 15);
     }
 
-    fn dead(&mut self, ctx: &mut BTerm) {
-        self.main_menu(ctx)
+    fn watch_replay(&mut self, ctx: &mut BTerm) {
+        let Some(player) = self.replay_player.as_mut() else {
+            self.game_mode = GameMode::Menu;
+            return;
+        };
+
+        if !player.handle_key(ctx.key) {
+            self.replay_player = None;
+            self.game_mode = GameMode::Menu;
+            return;
+        }
+        player.update(ctx.frame_time_ms);
+
+        ctx.cls_bg(BLACK);
+        render_board(ctx, &player.game, &self.menu.settings);
+
+        if let Some(death) = player.game.death {
+            ctx.print_color_centered(SCREEN_HEIGHT / 2, RED, BLACK, death.cause.describe());
+        }
+
+        ctx.print(0, 0, format!("Score: {}", player.game.score));
+        ctx.print_color(0, SCREEN_HEIGHT - 1, GREY, BLACK, player.status_line());
     }
 }
 
 impl GameState for State {
     fn tick(&mut self, ctx: &mut BTerm) {
+        // the HUD layer is only drawn on while playing
+        ctx.set_active_console(HUD_CONSOLE);
+        ctx.cls();
+        ctx.set_active_console(0);
+
         match self.game_mode {
             GameMode::Menu => self.main_menu(ctx),
             GameMode::End => self.dead(ctx),
             GameMode::Playing => self.play(ctx),
+            GameMode::Replay => self.watch_replay(ctx),
         }
     }
 }
 
+fn end_menu() -> Menu<EndChoice> {
+    Menu::new("What now?")
+        .with_item("Retry", EndChoice::Retry)
+        .with_item("Main Menu", EndChoice::Menu)
+}
+
 fn main() -> BError {
+    // `--replay <file>` starts straight into playback of a shared run
+    let args: Vec<String> = std::env::args().collect();
+    let replay = match args.iter().position(|arg| arg == "--replay") {
+        Some(index) => {
+            let path = args.get(index + 1).ok_or("--replay needs a file")?;
+            Some(Replay::load(path)?)
+        }
+        None => None,
+    };
+
     let context = BTermBuilder::simple80x50()
+        // sparse, because the crossterm backend draws every cell of a simple
+        // console, background or not, over the playfield
+        .with_sparse_console_no_bg(SCREEN_WIDTH, SCREEN_HEIGHT, "terminal8x8.png")
         .with_title("Ascii Snake Game")
         .build()?;
 
-    main_loop(context, State::new())
+    let mut state = State::new();
+    if let Some(replay) = replay {
+        state.watch(replay);
+    }
+
+    main_loop(context, state)
 }
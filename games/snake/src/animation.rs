@@ -0,0 +1,224 @@
+//! Tweened looks for the sprite board: snakes fading in when a run
+//! starts, the player flashing red and fading out segment by segment
+//! when it dies, and food gently pulsing.
+//!
+//! None of this touches the game itself. It only works out the tint
+//! and z-order rank handed to `add_sprite` for a given moment.
+
+use bracket_lib::prelude::*;
+
+/// Added to a segment's rank while it is animating, so it draws over
+/// whatever else is on the same row of the board.
+pub const ANIMATED_RANK: u8 = 8;
+
+/// Longest gap between two segments starting their animation. Long
+/// snakes get shorter gaps, so the whole body is done in [`STAGGER_MS`].
+const SEGMENT_DELAY_MS: f32 = 60.0;
+const STAGGER_MS: f32 = 600.0;
+
+const SPAWN: Tween = Tween::new(250.0, Ease::OutQuad);
+const DEATH_FLASH: Tween = Tween::new(120.0, Ease::OutQuad);
+const DEATH_FADE: Tween = Tween::new(400.0, Ease::InOutSine).after(DEATH_FLASH.duration_ms);
+const FOOD_PULSE_MS: f32 = 900.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Ease {
+    OutQuad,
+    InOutSine,
+}
+
+impl Ease {
+    /// Maps linear progress in 0..=1 onto the curve.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Ease::OutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Ease::InOutSine => (1.0 - (t * std::f32::consts::PI).cos()) / 2.0,
+        }
+    }
+}
+
+/// Progress through a timed change: waits `delay_ms`, then runs from 0
+/// to 1 over `duration_ms` along `ease`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Tween {
+    pub delay_ms: f32,
+    pub duration_ms: f32,
+    pub ease: Ease,
+}
+
+impl Tween {
+    pub const fn new(duration_ms: f32, ease: Ease) -> Self {
+        Self {
+            delay_ms: 0.0,
+            duration_ms,
+            ease,
+        }
+    }
+
+    /// The same tween, started `delay_ms` later.
+    pub const fn after(self, delay_ms: f32) -> Self {
+        Self {
+            delay_ms: self.delay_ms + delay_ms,
+            ..self
+        }
+    }
+
+    /// Eased progress `elapsed_ms` after the tween was set off.
+    pub fn at(&self, elapsed_ms: f32) -> f32 {
+        let t = (elapsed_ms - self.delay_ms) / self.duration_ms.max(f32::EPSILON);
+        self.ease.apply(t.clamp(0.0, 1.0))
+    }
+
+    pub fn end_ms(&self) -> f32 {
+        self.delay_ms + self.duration_ms
+    }
+}
+
+/// Everything the renderer needs to know to animate one frame.
+#[derive(Clone, Copy, Debug)]
+pub struct Animations {
+    /// Time since the run started.
+    pub clock_ms: f32,
+    /// Time since the player died, while the board lingers.
+    pub dying_ms: Option<f32>,
+    /// Off for reduced motion: no flash and no pulse, only fades.
+    pub lively: bool,
+}
+
+impl Animations {
+    /// A board that is simply shown as it is, e.g. during a replay.
+    pub fn still() -> Self {
+        Self {
+            clock_ms: f32::MAX,
+            dying_ms: None,
+            lively: false,
+        }
+    }
+
+    /// Tint and extra rank for segment `index` (0 is the head) of a snake
+    /// `length` long. Only the player can be `dying`: a CPU opponent
+    /// leaves the board as soon as it crashes.
+    pub fn segment(&self, index: usize, length: usize, tint: RGBA, dying: bool) -> (RGBA, u8) {
+        let delay = index as f32 * SEGMENT_DELAY_MS.min(STAGGER_MS / length.max(1) as f32);
+
+        if let (true, Some(dying_ms)) = (dying, self.dying_ms) {
+            let elapsed = dying_ms - delay;
+            let flashed = if self.lively {
+                tint.lerp(
+                    RGBA::from_f32(1.0, 0.15, 0.15, tint.a),
+                    DEATH_FLASH.at(elapsed),
+                )
+            } else {
+                tint
+            };
+            let faded =
+                flashed.lerp_alpha(RGBA::from_f32(0.0, 0.0, 0.0, 0.0), DEATH_FADE.at(elapsed));
+            return (faded, ANIMATED_RANK);
+        }
+
+        let elapsed = self.clock_ms - delay;
+        if elapsed >= SPAWN.end_ms() {
+            return (tint, 0);
+        }
+        let t = SPAWN.at(elapsed);
+        let from = if self.lively {
+            RGBA::from_f32(1.0, 1.0, 1.0, 0.0)
+        } else {
+            RGBA::from_f32(tint.r, tint.g, tint.b, 0.0)
+        };
+        (from.lerp(tint, t), ANIMATED_RANK)
+    }
+
+    /// Food swells in brightness and back, offset per cell so a crowded
+    /// board doesn't pulse in unison.
+    pub fn food(&self, cell: Point, tint: RGBA) -> RGBA {
+        if !self.lively {
+            return tint;
+        }
+        let phase = (cell.x * 7 + cell.y * 13) as f32 * 0.1;
+        let t = (self.clock_ms / FOOD_PULSE_MS + phase).fract();
+        // up then back down over one period
+        let glow = Ease::InOutSine.apply(1.0 - (2.0 * t - 1.0).abs());
+        let dim = 0.75 + 0.25 * glow;
+        RGBA::from_f32(tint.r * dim, tint.g * dim, tint.b * dim, tint.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TINT: RGBA = RGBA {
+        r: 0.2,
+        g: 0.8,
+        b: 0.4,
+        a: 1.0,
+    };
+
+    #[test]
+    fn eases_start_at_0_and_end_at_1() {
+        for ease in [Ease::OutQuad, Ease::InOutSine] {
+            assert_eq!(ease.apply(0.0), 0.0, "{:?}", ease);
+            assert!((ease.apply(1.0) - 1.0).abs() < 1e-6, "{:?}", ease);
+            assert!(ease.apply(0.5) > 0.0 && ease.apply(0.5) < 1.0);
+        }
+    }
+
+    #[test]
+    fn tweens_wait_their_delay_then_hold_at_the_end() {
+        let tween = Tween::new(200.0, Ease::OutQuad).after(100.0);
+        assert_eq!(tween.end_ms(), 300.0);
+        assert_eq!(tween.at(0.0), 0.0);
+        assert_eq!(tween.at(100.0), 0.0);
+        assert!(tween.at(200.0) > 0.0 && tween.at(200.0) < 1.0);
+        assert_eq!(tween.at(300.0), 1.0);
+        assert_eq!(tween.at(10_000.0), 1.0);
+    }
+
+    #[test]
+    fn segments_settle_once_spawned() {
+        let animations = Animations {
+            clock_ms: 0.0,
+            dying_ms: None,
+            lively: true,
+        };
+        let (tint, rank) = animations.segment(0, 3, TINT, false);
+        assert_eq!((tint.a, rank), (0.0, ANIMATED_RANK));
+
+        let settled = Animations {
+            clock_ms: STAGGER_MS + SPAWN.end_ms(),
+            ..animations
+        };
+        for index in 0..3 {
+            assert_eq!(settled.segment(index, 3, TINT, false), (TINT, 0));
+        }
+        assert_eq!(Animations::still().segment(2, 3, TINT, false), (TINT, 0));
+    }
+
+    #[test]
+    fn dying_segments_fade_out() {
+        let dying = Animations {
+            clock_ms: 10_000.0,
+            dying_ms: Some(STAGGER_MS + DEATH_FADE.end_ms()),
+            lively: true,
+        };
+        for index in 0..3 {
+            let (tint, rank) = dying.segment(index, 3, TINT, true);
+            assert_eq!((tint.a, rank), (0.0, ANIMATED_RANK));
+        }
+        // the opponent isn't dying, so it stays as it is
+        assert_eq!(dying.segment(0, 3, TINT, false), (TINT, 0));
+    }
+
+    #[test]
+    fn reduced_motion_skips_the_flash_and_pulse() {
+        let calm = Animations {
+            clock_ms: 10_000.0,
+            dying_ms: Some(DEATH_FLASH.end_ms()),
+            lively: false,
+        };
+        let (tint, _) = calm.segment(0, 3, TINT, true);
+        assert_eq!((tint.r, tint.g, tint.b), (TINT.r, TINT.g, TINT.b));
+        assert_eq!(calm.food(Point::new(4, 2), TINT), TINT);
+    }
+}
@@ -0,0 +1,277 @@
+//! Particles and screen shake drawn over the sprite board.
+//!
+//! Particles live in board cells rather than pixels, so they stay put
+//! when the window is resized, and everything is advanced by the frame's
+//! real time rather than by game ticks. With `enabled` off nothing is
+//! spawned and the camera holds still.
+
+use bracket_lib::prelude::*;
+use snake_common::layers::Layer;
+
+use crate::layout::Layout;
+
+/// Cells per second squared pulling debris down.
+const GRAVITY: f32 = 18.0;
+
+/// What a kind of particle looks like and how long it lasts.
+struct Style {
+    /// Fraction of a cell it covers.
+    size: f32,
+    falls: bool,
+    life_ms: f32,
+}
+
+const FOOD_BITS: Style = Style {
+    size: 0.25,
+    falls: false,
+    life_ms: 450.0,
+};
+const DEBRIS: Style = Style {
+    size: 0.35,
+    falls: true,
+    life_ms: 1100.0,
+};
+const TRAIL: Style = Style {
+    size: 0.3,
+    falls: false,
+    life_ms: 300.0,
+};
+
+struct Particle {
+    /// Centre, in board cells.
+    x: f32,
+    y: f32,
+    /// Cells per second.
+    dx: f32,
+    dy: f32,
+    /// Fraction of a cell it covers.
+    size: f32,
+    falls: bool,
+    age_ms: f32,
+    life_ms: f32,
+    sprite: usize,
+    tint: RGBA,
+}
+
+impl Particle {
+    fn alpha(&self) -> f32 {
+        (1.0 - self.age_ms / self.life_ms).clamp(0.0, 1.0)
+    }
+}
+
+pub struct Effects {
+    pub enabled: bool,
+    /// Sprites food bits and snake pieces are drawn with.
+    food_sprite: usize,
+    body_sprite: usize,
+    particles: Vec<Particle>,
+    shake_ms: f32,
+    shake_total_ms: f32,
+    /// Strongest offset of the shake, in pixels.
+    shake_strength: f32,
+    shake_offset: Point,
+    rng: RandomNumberGenerator,
+}
+
+impl Effects {
+    pub fn new(food_sprite: usize, body_sprite: usize) -> Self {
+        Self {
+            enabled: true,
+            food_sprite,
+            body_sprite,
+            particles: Vec::new(),
+            shake_ms: 0.0,
+            shake_total_ms: 0.0,
+            shake_strength: 0.0,
+            shake_offset: Point::zero(),
+            rng: RandomNumberGenerator::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.shake_ms = 0.0;
+        self.shake_offset = Point::zero();
+    }
+
+    /// Bits of food flying out of `cell` when it is eaten.
+    pub fn burst(&mut self, cell: Point, tint: RGBA) {
+        for _ in 0..12 {
+            let angle = self.rng.range(0.0, std::f32::consts::TAU);
+            let speed = self.rng.range(2.0, 6.0);
+            self.spawn(cell, angle, speed, &FOOD_BITS, self.food_sprite, tint);
+        }
+    }
+
+    /// Pieces of every segment scattering when a snake dies.
+    pub fn debris(&mut self, body: &[Point], tint: RGBA) {
+        for cell in body {
+            for _ in 0..3 {
+                // mostly upwards, so gravity can bring them back down
+                let angle = self.rng.range(3.6, 5.8);
+                let speed = self.rng.range(3.0, 9.0);
+                self.spawn(*cell, angle, speed, &DEBRIS, self.body_sprite, tint);
+            }
+        }
+    }
+
+    /// A faint puff left in the cell the head just moved out of.
+    pub fn trail(&mut self, cell: Point, tint: RGBA) {
+        let tint = RGBA::from_f32(tint.r, tint.g, tint.b, 0.5);
+        let angle = self.rng.range(0.0, std::f32::consts::TAU);
+        self.spawn(cell, angle, 0.4, &TRAIL, self.body_sprite, tint);
+    }
+
+    /// Shakes the camera by up to `strength` pixels, easing off over
+    /// `duration_ms`. A weaker shake doesn't cut a stronger one short.
+    pub fn shake(&mut self, strength: f32, duration_ms: f32) {
+        if !self.enabled || strength < self.shake_strength * self.shake_left() {
+            return;
+        }
+        self.shake_strength = strength;
+        self.shake_ms = duration_ms;
+        self.shake_total_ms = duration_ms;
+    }
+
+    /// Where the board should be drawn relative to its resting place.
+    pub fn shake_offset(&self) -> Point {
+        self.shake_offset
+    }
+
+    pub fn update(&mut self, frame_time_ms: f32) {
+        let seconds = frame_time_ms / 1000.0;
+        for particle in &mut self.particles {
+            particle.age_ms += frame_time_ms;
+            if particle.falls {
+                particle.dy += GRAVITY * seconds;
+            }
+            particle.x += particle.dx * seconds;
+            particle.y += particle.dy * seconds;
+        }
+        self.particles.retain(|p| p.age_ms < p.life_ms);
+
+        self.shake_ms = (self.shake_ms - frame_time_ms).max(0.0);
+        let strength = self.shake_strength * self.shake_left();
+        self.shake_offset = if strength >= 1.0 {
+            Point::new(
+                self.rng.range(-strength, strength) as i32,
+                self.rng.range(-strength, strength) as i32,
+            )
+        } else {
+            Point::zero()
+        };
+    }
+
+    pub fn render(&self, ctx: &mut BTerm, layout: &Layout) {
+        let tile = layout.tile as f32;
+        for particle in &self.particles {
+            let size = (particle.size * tile).max(2.0);
+            let x = layout.origin.x as f32 + particle.x * tile - size / 2.0;
+            let y = layout.origin.y as f32 + particle.y * tile - size / 2.0;
+            let tint = RGBA::from_f32(
+                particle.tint.r,
+                particle.tint.g,
+                particle.tint.b,
+                particle.tint.a * particle.alpha(),
+            );
+            ctx.add_sprite(
+                Rect::with_size(x as i32, y as i32, size as i32, size as i32),
+                Layer::Effects.z(),
+                tint,
+                particle.sprite,
+            );
+        }
+    }
+
+    fn shake_left(&self) -> f32 {
+        if self.shake_total_ms > 0.0 {
+            self.shake_ms / self.shake_total_ms
+        } else {
+            0.0
+        }
+    }
+
+    fn spawn(
+        &mut self,
+        cell: Point,
+        angle: f32,
+        speed: f32,
+        style: &Style,
+        sprite: usize,
+        tint: RGBA,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let life_ms = style.life_ms * self.rng.range(0.6, 1.0);
+        self.particles.push(Particle {
+            x: cell.x as f32 + 0.5,
+            y: cell.y as f32 + 0.5,
+            dx: angle.cos() * speed,
+            dy: angle.sin() * speed,
+            size: style.size,
+            falls: style.falls,
+            age_ms: 0.0,
+            life_ms,
+            sprite,
+            tint,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: RGBA = RGBA {
+        r: 1.0,
+        g: 1.0,
+        b: 1.0,
+        a: 1.0,
+    };
+
+    #[test]
+    fn shake_eases_off_to_nothing() {
+        let mut effects = Effects::new(0, 1);
+        effects.shake(10.0, 300.0);
+        effects.update(16.0);
+        let offset = effects.shake_offset();
+        assert!(offset.x.abs() <= 10 && offset.y.abs() <= 10, "{:?}", offset);
+
+        effects.update(150.0);
+        assert!(effects.shake_left() > 0.0 && effects.shake_left() < 0.5);
+        effects.update(150.0);
+        assert_eq!(effects.shake_left(), 0.0);
+        assert_eq!(effects.shake_offset(), Point::zero());
+    }
+
+    #[test]
+    fn weaker_shakes_dont_cut_a_strong_one_short() {
+        let mut effects = Effects::new(0, 1);
+        effects.shake(10.0, 300.0);
+        effects.shake(2.0, 50.0);
+        assert_eq!(effects.shake_ms, 300.0);
+    }
+
+    #[test]
+    fn reduced_motion_holds_still() {
+        let mut effects = Effects::new(0, 1);
+        effects.enabled = false;
+        effects.shake(10.0, 300.0);
+        effects.burst(Point::new(3, 3), WHITE);
+        effects.debris(&[Point::new(1, 1), Point::new(2, 1)], WHITE);
+        effects.update(16.0);
+        assert_eq!(effects.shake_offset(), Point::zero());
+        assert!(effects.particles.is_empty());
+    }
+
+    #[test]
+    fn particles_die_out() {
+        let mut effects = Effects::new(0, 1);
+        effects.burst(Point::new(3, 3), WHITE);
+        effects.trail(Point::new(2, 3), WHITE);
+        assert_eq!(effects.particles.len(), 13);
+        effects.update(FOOD_BITS.life_ms);
+        assert!(effects.particles.is_empty());
+    }
+}
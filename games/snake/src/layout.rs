@@ -0,0 +1,87 @@
+//! Fits the board and the HUD to whatever size the window currently is.
+//!
+//! Console 0 is a sprite console measured in window pixels and console 1
+//! is the text HUD, measured in [`HUD_GLYPH`] sized characters. Both are
+//! resized whenever the window is.
+
+use bracket_lib::prelude::*;
+use snake_common::sheet::SpriteFrame;
+
+/// On-screen size of a HUD character in pixels: the 8x8 font drawn at
+/// twice its natural size so it stays readable on large windows.
+pub const HUD_GLYPH: u32 = 16;
+
+/// Rows a freshly started board aims for. The tile size follows from the
+/// window height, and the number of columns from the tile size.
+const TARGET_ROWS: i32 = 30;
+const MIN_TILE: i32 = 16;
+const MAX_TILE: i32 = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Layout {
+    /// Edge length of a board cell in pixels.
+    pub tile: i32,
+    /// Top left corner of the board in pixels.
+    pub origin: Point,
+}
+
+impl Layout {
+    /// Board dimensions for a game started in a window of this size.
+    pub fn board_for(window: (u32, u32)) -> (i32, i32) {
+        let (width, height) = (window.0 as i32, window.1 as i32);
+        let tile = (height / TARGET_ROWS).clamp(MIN_TILE, MAX_TILE);
+        ((width / tile).max(4), (height / tile).max(4))
+    }
+
+    /// The largest whole tile size that shows all of `board`, with the
+    /// board centered in the window. Used for games already in progress,
+    /// whose board can't change size with the window.
+    pub fn fit(window: (u32, u32), board: (i32, i32)) -> Self {
+        let (width, height) = (window.0 as i32, window.1 as i32);
+        let tile = (width / board.0).min(height / board.1).max(1);
+        Self {
+            tile,
+            origin: Point::new((width - tile * board.0) / 2, (height - tile * board.1) / 2),
+        }
+    }
+
+    /// The same layout moved by `offset` pixels, e.g. for screen shake.
+    pub fn shifted(self, offset: Point) -> Self {
+        Self {
+            origin: self.origin + offset,
+            ..self
+        }
+    }
+
+    /// Where to draw a sprite standing in `cell`: as large as fits the
+    /// cell without stretching, with its pivot on the matching point of
+    /// the cell, so a centred sprite sits in the middle and one pivoted
+    /// on its feet stands on the cell's bottom edge.
+    pub fn sprite_rect(&self, cell: &Point, frame: &SpriteFrame) -> Rect {
+        let tile = self.tile as f32;
+        let at = PointF::new(
+            self.origin.x as f32 + (cell.x as f32 + frame.pivot.x) * tile,
+            self.origin.y as f32 + (cell.y as f32 + frame.pivot.y) * tile,
+        );
+        frame.place(at, frame.fit(tile, tile))
+    }
+}
+
+/// Size of the HUD console, in characters, for a window of this size.
+pub fn hud_size(window: (u32, u32)) -> (u32, u32) {
+    ((window.0 / HUD_GLYPH).max(1), (window.1 / HUD_GLYPH).max(1))
+}
+
+/// Matches both consoles to a new window size. The browser canvas never
+/// changes size, so there is nothing to do there.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn resize_consoles(ctx: &mut BTerm, window: (u32, u32)) {
+    let (columns, rows) = hud_size(window);
+    ctx.set_active_console(0);
+    ctx.set_char_size(window.0, window.1);
+    ctx.set_active_console(1);
+    ctx.set_char_size(columns, rows);
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn resize_consoles(_ctx: &mut BTerm, _window: (u32, u32)) {}
@@ -1,6 +1,25 @@
+mod animation;
+mod effects;
+mod layout;
+
+use std::path::Path;
 use std::unreachable;
 
+use animation::Animations;
 use bracket_lib::prelude::*;
+use effects::Effects;
+use layout::{Layout, HUD_GLYPH};
+use snake_common::ai::{self, AStarController, Controller};
+use snake_common::aseprite;
+use snake_common::assets::{self, Asset};
+use snake_common::layers::Layer;
+use snake_common::sheet::SpriteIndex;
+use snake_common::{
+    Direction, FoodKind, Game, GameConfig, HighScores, Hud, MainMenu, MenuAction, Replay,
+    ReplayPlayer, Settings, Snake, SnakeId, SpawnPolicy, StepEvent,
+};
+
+add_wasm_support!();
 
 const SCREEN_WIDTH: u32 = 1800;
 const SCREEN_HEIGHT: u32 = 1200;
@@ -8,24 +27,27 @@ const TILE_W: u32 = 8;
 const TILE_H: u32 = 8;
 const SPRITE_TILE_SIZE: i32 = 40;
 const DEFAULT_SNAKE_LENGTH: i32 = 3;
-// TODO:
-// const FRAME_DURATION: f32 = 75.0;
-
-enum Direction {
-    Up,
-    Down,
-    Right,
-    Left,
-}
-
-fn rects_overlap(ax: i32, ay: i32, aw: i32, ah: i32, bx: i32, by: i32, bw: i32, bh: i32) -> bool {
-    !(ax + aw <= bx  ||  // A is completely left of B
-      ax >= bx + bw  ||  // A is completely right of B
-      ay + ah <= by  ||  // A is completely above B
-      ay >= by + bh) // A is completely below B
-}
-
-fn direction_between(a: &SnakePart, b: &SnakePart) -> Direction {
+const LAST_RUN_REPLAY: &str = "snake_last.replay";
+/// How long the board stays up after a death, so the debris can settle.
+const DEATH_LINGER_MS: f32 = 1200.0;
+
+const GAME_CONFIG: GameConfig = GameConfig {
+    width: SCREEN_WIDTH as i32 / SPRITE_TILE_SIZE,
+    height: SCREEN_HEIGHT as i32 / SPRITE_TILE_SIZE,
+    initial_length: DEFAULT_SNAKE_LENGTH,
+    opponent: false,
+    power_ups: false,
+    spawn: SpawnPolicy::CLASSIC,
+};
+
+// which of two board sprites in the same row draws on top: a head over
+// the body it turns from, and any snake over food it is passing.
+// Animating segments go above all of these, see `animation::ANIMATED_RANK`
+const FOOD_RANK: u8 = 0;
+const BODY_RANK: u8 = 1;
+const HEAD_RANK: u8 = 2;
+
+fn direction_between(a: &Point, b: &Point) -> Direction {
     match (b.x.cmp(&a.x), b.y.cmp(&a.y)) {
         (std::cmp::Ordering::Greater, std::cmp::Ordering::Equal) => Direction::Right,
         (std::cmp::Ordering::Less, std::cmp::Ordering::Equal) => Direction::Left,
@@ -36,465 +58,609 @@ fn direction_between(a: &SnakePart, b: &SnakePart) -> Direction {
     }
 }
 
-struct Food {
-    pos_x: i32,
-    pos_y: i32,
+/// Every kind of food shares the apple sprite, told apart by colour.
+fn food_tint(kind: FoodKind, tint: RGBA) -> RGBA {
+    match kind {
+        FoodKind::Normal => tint,
+        FoodKind::Golden => RGBA::from_f32(1.0, 0.85, 0.2, 1.0),
+        FoodKind::SlowDown => RGBA::from_f32(0.4, 0.6, 1.0, 1.0),
+        FoodKind::SpeedUp => RGBA::from_f32(1.0, 0.4, 0.3, 1.0),
+        FoodKind::Shrink => RGBA::from_f32(0.6, 0.6, 0.6, 1.0),
+        FoodKind::Ghost => RGBA::from_f32(0.8, 0.6, 1.0, 0.6),
+    }
 }
 
-impl Food {
-    fn new() -> Self {
-        Self {
-            pos_x: RandomNumberGenerator::new().range(0, SCREEN_WIDTH as i32 - SPRITE_TILE_SIZE),
-            pos_y: RandomNumberGenerator::new().range(0, SCREEN_HEIGHT as i32 - SPRITE_TILE_SIZE),
+fn render_food(
+    ctx: &mut BTerm,
+    game: &Game,
+    layout: &Layout,
+    snake_tiles: &SnakeParts,
+    settings: &Settings,
+    animations: &Animations,
+) {
+    for food in &game.foods {
+        if !settings.reduced_motion && food.is_expiring(game.ticks) && game.ticks % 2 == 1 {
+            continue;
         }
+        let tint = food_tint(food.kind, settings.theme.sprite_tint());
+        let tint = animations.food(food.cell, tint);
+        let rect = snake_tiles.place(layout, &food.cell, snake_tiles.apple);
+        ctx.add_sprite(
+            rect,
+            Layer::Entities.y_sorted(rect, FOOD_RANK),
+            tint,
+            snake_tiles.apple,
+        );
     }
+}
 
-    fn render(&mut self, ctx: &mut BTerm) {
-        ctx.add_sprite(
-            Rect::with_size(
-                self.pos_x as i32,
-                self.pos_y as i32,
-                SPRITE_TILE_SIZE,
-                SPRITE_TILE_SIZE,
-            ),
-            400,
-            RGBA::from_f32(1.0, 1.0, 1.0, 1.0),
-            14,
+fn render_board(
+    ctx: &mut BTerm,
+    game: &Game,
+    layout: &Layout,
+    snake_tiles: &SnakeParts,
+    settings: &Settings,
+    animations: &Animations,
+) {
+    let theme = settings.theme;
+    render_food(ctx, game, layout, snake_tiles, settings, animations);
+    if let Some(opponent) = &game.opponent {
+        let look = |index, tint| animations.segment(index, opponent.length(), tint, false);
+        render_snake(
+            ctx,
+            opponent,
+            layout,
+            snake_tiles,
+            theme.opponent_tint(),
+            look,
         );
     }
+    let look = |index, tint| animations.segment(index, game.snake.length(), tint, true);
+    render_snake(
+        ctx,
+        &game.snake,
+        layout,
+        snake_tiles,
+        theme.sprite_tint(),
+        look,
+    );
+}
+
+/// Draws `snake` with `look` giving the tint and extra rank of each
+/// segment from its index and the snake's base tint.
+fn render_snake(
+    ctx: &mut BTerm,
+    snake: &Snake,
+    layout: &Layout,
+    snake_tiles: &SnakeParts,
+    tint: RGBA,
+    look: impl Fn(usize, RGBA) -> (RGBA, u8),
+) {
+    for (index, part) in snake.body.iter().enumerate() {
+        let is_tail = index == snake.length() - 1;
+        let is_head = index == 0;
+        let (tint, lift) = look(index, tint);
+        let rank = (if is_head { HEAD_RANK } else { BODY_RANK }) + lift;
+        let mut draw = |sprite| {
+            let rect = snake_tiles.place(layout, part, sprite);
+            ctx.add_sprite(rect, Layer::Entities.y_sorted(rect, rank), tint, sprite);
+        };
+
+        if (is_head) {
+            let sprite_index = match snake.direction {
+                Direction::Right => snake_tiles.head_right,
+                Direction::Down => snake_tiles.head_down,
+                Direction::Left => snake_tiles.head_left,
+                Direction::Up => snake_tiles.head_up,
+            };
+
+            draw(sprite_index);
+        } else if (is_tail) {
+            let next_part = &snake.body[index - 1];
+
+            let sprite_index = match (next_part.x.cmp(&part.x), next_part.y.cmp(&part.y)) {
+                (std::cmp::Ordering::Greater, std::cmp::Ordering::Equal) => snake_tiles.tail_left,
+
+                (std::cmp::Ordering::Less, std::cmp::Ordering::Equal) => snake_tiles.tail_right,
+
+                (std::cmp::Ordering::Equal, std::cmp::Ordering::Greater) => snake_tiles.tail_up,
+                (std::cmp::Ordering::Equal, std::cmp::Ordering::Less) => snake_tiles.tail_down,
+                _ => unreachable!("the tail is always one step from the segment before it"),
+            };
+
+            draw(sprite_index);
+        } else {
+            let next = &snake.body[index - 1];
+            let prev = &snake.body[index + 1];
+
+            let from_prev = direction_between(prev, part);
+            let to_next = direction_between(part, next);
+
+            let sprite_index = match (from_prev, to_next) {
+                (Direction::Up, Direction::Down)
+                | (Direction::Down, Direction::Up)
+                | (Direction::Up, Direction::Up)
+                | (Direction::Down, Direction::Down) => snake_tiles.body_vertical,
+
+                (Direction::Left, Direction::Right)
+                | (Direction::Right, Direction::Left)
+                | (Direction::Right, Direction::Right)
+                | (Direction::Left, Direction::Left) => snake_tiles.body_horizontal,
+
+                // Corners
+                (Direction::Up, Direction::Right) | (Direction::Left, Direction::Down) => {
+                    snake_tiles.body_bottomright
+                }
+
+                (Direction::Up, Direction::Left) | (Direction::Right, Direction::Down) => {
+                    snake_tiles.body_bottomleft
+                }
+
+                (Direction::Right, Direction::Up) | (Direction::Down, Direction::Left) => {
+                    snake_tiles.body_topleft
+                }
+
+                (Direction::Down, Direction::Right) | (Direction::Left, Direction::Up) => {
+                    snake_tiles.body_topright
+                }
+
+                _ => unreachable!("straight segments already handled"),
+            };
+
+            draw(sprite_index);
+        }
+    }
 }
 
-struct DirectionVector {
-    x: i32,
-    y: i32,
+// TODO: End and Pause modes
+enum GameMode {
+    Menu,
+    Playing,
+    /// The run just ended; the board stays up while the debris settles.
+    Dying,
+    Replay,
 }
 
-struct SnakePart {
-    x: i32,
-    y: i32,
+#[derive(Clone, Copy, PartialEq)]
+enum PlayMode {
+    Solo,
+    VersusCpu,
+    /// The autopilot plays on its own until a key is pressed.
+    Demo,
 }
 
-struct Snake {
-    speed: i32,
-    direction_vector: DirectionVector,
-    snake_parts: Vec<SnakePart>,
+struct State {
+    game_mode: GameMode,
+    menu: MainMenu,
+    game: Game,
+    play_mode: PlayMode,
+    replay: Replay,
+    replay_player: Option<ReplayPlayer>,
+    next_direction: Option<Direction>,
     snake_tiles: SnakeParts,
+    hud: Hud,
+    effects: Effects,
+    time_alive_ms: f32,
+    dying_ms: f32,
+    /// Window size in pixels as of the last tick.
+    window: (u32, u32),
+    timer: f32,
 }
 
-impl Snake {
-    fn new(head_pos_x: i32, head_pos_y: i32) -> Self {
-        Snake {
-            snake_parts: (0..DEFAULT_SNAKE_LENGTH)
-                .map(|i| SnakePart {
-                    x: head_pos_x - i * SPRITE_TILE_SIZE,
-                    y: head_pos_y,
-                })
-                .collect(),
-            direction_vector: DirectionVector { x: 1, y: 0 },
-            speed: SPRITE_TILE_SIZE,
-            snake_tiles: SnakeParts::new(),
+impl State {
+    fn new(snake_tiles: SnakeParts) -> Self {
+        let effects = Effects::new(snake_tiles.apple, snake_tiles.body_horizontal);
+        Self {
+            game_mode: GameMode::Menu,
+            menu: MainMenu::new("Sprite Snake", HighScores::load("snake_scores.txt")),
+            game: Game::new(GAME_CONFIG, 0),
+            play_mode: PlayMode::Solo,
+            replay: Replay::new(GAME_CONFIG, 0, 0.0),
+            replay_player: None,
+            next_direction: None,
+            snake_tiles,
+            hud: Hud::new(),
+            effects,
+            time_alive_ms: 0.0,
+            dying_ms: 0.0,
+            window: (SCREEN_WIDTH, SCREEN_HEIGHT),
+            timer: 0.0,
         }
     }
 
-    fn change_direction(&mut self, direction: Direction) {
-        self.direction_vector = match direction {
-            Direction::Up => DirectionVector { x: 0, y: -1 },
-            Direction::Down => DirectionVector { x: 0, y: 1 },
-            Direction::Left => DirectionVector { x: -1, y: 0 },
-            Direction::Right => DirectionVector { x: 1, y: 0 },
-        };
-    }
+    fn main_menu(&mut self, ctx: &mut BTerm) {
+        ctx.set_active_console(0);
+        ctx.cls();
+        ctx.set_active_console(1);
 
-    fn slither(&mut self) {
-        if self.speed == 0 {
-            return;
+        match self.menu.tick(ctx) {
+            Some(MenuAction::Play) => self.start(PlayMode::Solo),
+            Some(MenuAction::PlayVersusCpu) => self.start(PlayMode::VersusCpu),
+            Some(MenuAction::Demo) => self.start(PlayMode::Demo),
+            Some(MenuAction::WatchReplay) => match Replay::load(LAST_RUN_REPLAY) {
+                Ok(replay) => self.watch(replay),
+                Err(err) => self
+                    .menu
+                    .report(format!("Can't watch the last run: {}", err)),
+            },
+            Some(MenuAction::Quit) => ctx.quitting = true,
+            None => {}
         }
+    }
 
-        let new_head = SnakePart {
-            x: self.snake_parts[0].x + self.direction_vector.x * self.speed,
-            y: self.snake_parts[0].y + self.direction_vector.y * self.speed,
+    fn start(&mut self, play_mode: PlayMode) {
+        let seed = RandomNumberGenerator::new().next_u64();
+        let frame_duration = self.menu.settings.difficulty.frame_duration();
+        let (width, height) = Layout::board_for(self.window);
+        let config = GameConfig {
+            width,
+            height,
+            opponent: play_mode == PlayMode::VersusCpu,
+            power_ups: self.menu.settings.power_ups,
+            spawn: self.menu.settings.food_mode.spawn_policy(),
+            ..GAME_CONFIG
         };
 
-        self.snake_parts.insert(0, new_head);
-        self.snake_parts.pop();
+        self.game_mode = GameMode::Playing;
+        self.play_mode = play_mode;
+        self.timer = 0.0;
+        self.time_alive_ms = 0.0;
+        self.next_direction = None;
+        self.effects.clear();
+        self.game = Game::new(config, seed);
+        self.replay = Replay::new(config, seed, frame_duration);
     }
 
-    fn grow(&mut self) {
-        let new_head = SnakePart {
-            x: self.snake_parts[0].x + self.direction_vector.x * self.speed,
-            y: self.snake_parts[0].y + self.direction_vector.y * self.speed,
-        };
+    fn watch(&mut self, replay: Replay) {
+        self.replay_player = Some(ReplayPlayer::new(replay));
+        self.game_mode = GameMode::Replay;
+    }
+
+    fn game_over(&mut self) {
+        if self.play_mode == PlayMode::Demo {
+            self.start(PlayMode::Demo);
+            return;
+        }
 
-        self.snake_parts.insert(0, new_head);
+        self.menu.high_scores.submit(self.game.score);
+        self.replay.finish(self.game.ticks);
+        self.replay.save(LAST_RUN_REPLAY);
+        self.game_mode = GameMode::Menu;
     }
 
-    fn render(&mut self, ctx: &mut BTerm) {
-        for (index, part) in self.snake_parts.iter().enumerate() {
-            let is_tail = index == self.snake_parts.len() - 1;
-            let is_head = index == 0;
-
-            if (is_head) {
-                let sprite_index = match self.direction_vector {
-                    DirectionVector { x: 1, y: 0 } => self.snake_tiles.head_right.index,
-                    DirectionVector { x: 0, y: 1 } => self.snake_tiles.head_down.index,
-                    DirectionVector { x: -1, y: 0 } => self.snake_tiles.head_left.index,
-                    DirectionVector { x: 0, y: -1 } => self.snake_tiles.head_up.index,
-                    _ => 14,
-                };
-
-                ctx.add_sprite(
-                    Rect::with_size(part.x, part.y, SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
-                    400,
-                    RGBA::from_f32(1.0, 1.0, 1.0, 1.0),
-                    sprite_index,
-                );
-            } else if (is_tail) {
-                let next_part = &self.snake_parts[index - 1];
-
-                let sprite_index = match (next_part.x.cmp(&part.x), next_part.y.cmp(&part.y)) {
-                    (std::cmp::Ordering::Greater, std::cmp::Ordering::Equal) => {
-                        self.snake_tiles.tail_left.index
-                    }
+    fn play(&mut self, ctx: &mut BTerm) {
+        let settings = self.menu.settings;
 
-                    (std::cmp::Ordering::Less, std::cmp::Ordering::Equal) => {
-                        self.snake_tiles.tail_right.index
-                    }
+        ctx.set_active_console(1);
+        ctx.cls();
+        ctx.set_active_console(0);
+        ctx.cls();
 
-                    (std::cmp::Ordering::Equal, std::cmp::Ordering::Greater) => {
-                        self.snake_tiles.tail_up.index
-                    }
-                    (std::cmp::Ordering::Equal, std::cmp::Ordering::Less) => {
-                        self.snake_tiles.tail_down.index
-                    }
-                    _ => 14,
-                };
-
-                ctx.add_sprite(
-                    Rect::with_size(part.x, part.y, SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
-                    400,
-                    RGBA::from_f32(1.0, 1.0, 1.0, 1.0),
-                    sprite_index,
-                );
-            } else {
-                let next = &self.snake_parts[index - 1];
-                let prev = &self.snake_parts[index + 1];
-
-                let from_prev = direction_between(prev, part);
-                let to_next = direction_between(part, next);
-
-                let sprite_index = match (from_prev, to_next) {
-                    (Direction::Up, Direction::Down)
-                    | (Direction::Down, Direction::Up)
-                    | (Direction::Up, Direction::Up)
-                    | (Direction::Down, Direction::Down) => self.snake_tiles.body_vertical.index,
-
-                    (Direction::Left, Direction::Right)
-                    | (Direction::Right, Direction::Left)
-                    | (Direction::Right, Direction::Right)
-                    | (Direction::Left, Direction::Left) => self.snake_tiles.body_horizontal.index,
-
-                    // Corners
-                    (Direction::Up, Direction::Right) | (Direction::Left, Direction::Down) => {
-                        self.snake_tiles.body_bottomright.index
-                    }
+        if self.play_mode == PlayMode::Demo && ctx.key.is_some() {
+            self.game_mode = GameMode::Menu;
+            return;
+        }
 
-                    (Direction::Up, Direction::Left) | (Direction::Right, Direction::Down) => {
-                        self.snake_tiles.body_bottomleft.index
+        if self.hud.handle_key(ctx.key) {
+            // the key only toggled part of the HUD
+        } else if let Some(key) = ctx.key {
+            match key {
+                VirtualKeyCode::Q => {
+                    self.game.resign();
+                    self.game_over();
+                    return;
+                }
+                key => {
+                    if let Some(direction) = settings.controls.direction(key) {
+                        self.next_direction = Some(direction)
                     }
+                }
+            }
+        }
 
-                    (Direction::Right, Direction::Up) | (Direction::Down, Direction::Left) => {
-                        self.snake_tiles.body_topleft.index
-                    }
+        self.timer += ctx.frame_time_ms;
+        self.time_alive_ms += ctx.frame_time_ms;
 
-                    (Direction::Down, Direction::Right) | (Direction::Left, Direction::Up) => {
-                        self.snake_tiles.body_topright.index
-                    }
+        if self.timer > settings.difficulty.frame_duration_for(&self.game) {
+            self.timer = 0.0;
 
-                    _ => unreachable!("straight segments already handled"),
-                };
+            // snake move
+            let input = match self.play_mode {
+                PlayMode::Demo => Some(AStarController.next_direction(&self.game, SnakeId::Player)),
+                _ => self.next_direction.take(),
+            };
+            let opponent_input = ai::opponent_move(&self.game);
+            self.replay.record(self.game.ticks, input);
+            self.step(input, opponent_input);
+        }
 
-                ctx.add_sprite(
-                    Rect::with_size(part.x, part.y, 40, 40),
-                    400,
-                    RGBA::from_f32(1.0, 1.0, 1.0, 1.0),
-                    sprite_index,
-                );
-            }
+        // drawn after the step so the score and length match the board
+        ctx.set_active_console(1);
+        let (_, rows) = layout::hud_size(self.window);
+        self.hud.render(
+            ctx,
+            1,
+            &self.game,
+            self.menu.high_scores.best(),
+            self.time_alive_ms,
+        );
+        if self.play_mode == PlayMode::Demo {
+            ctx.print_centered(rows as i32 - 2, "DEMO - press any key");
         }
+
+        ctx.set_active_console(0);
+        self.effects.enabled = !settings.reduced_motion;
+        self.effects.update(ctx.frame_time_ms);
+        self.render_playfield(ctx);
     }
-}
 
-//  TODO:
-// enum GameMode {
-//   Playing,
-//   End,
-//   Menu
-//   Pause
-// }
+    /// Advances the game one move and sets off the particles and shake
+    /// that go with whatever happened.
+    fn step(&mut self, input: Option<Direction>, opponent_input: Option<Direction>) {
+        let theme = self.menu.settings.theme;
+        let foods = self.game.foods.clone();
+        let previous_head = self.game.snake.head();
+        let opponent_before = self
+            .game
+            .opponent
+            .as_ref()
+            .map(|o| (o.body.clone(), self.game.opponent_score));
+
+        let event = self.game.step_versus(input, opponent_input);
+
+        match event {
+            StepEvent::Ate => {
+                let head = self.game.snake.head();
+                if let Some(food) = foods.iter().find(|food| food.cell == head) {
+                    self.effects
+                        .burst(head, food_tint(food.kind, theme.sprite_tint()));
+                }
+                self.effects.shake(3.0, 120.0);
+                self.effects.trail(previous_head, theme.sprite_tint());
+            }
+            StepEvent::Moved => self.effects.trail(previous_head, theme.sprite_tint()),
+            StepEvent::Died(_) => {
+                self.effects
+                    .debris(&self.game.snake.body, theme.sprite_tint());
+                self.effects.shake(12.0, 400.0);
+            }
+        }
 
-struct State {
-    food: Food,
-    snake: Snake,
-    frame: usize,
-    timer: f32,
-}
+        if let Some((body, score)) = opponent_before {
+            match &self.game.opponent {
+                Some(opponent) if self.game.opponent_score > score => {
+                    let head = opponent.head();
+                    if let Some(food) = foods.iter().find(|food| food.cell == head) {
+                        self.effects
+                            .burst(head, food_tint(food.kind, theme.sprite_tint()));
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    self.effects.debris(&body, theme.opponent_tint());
+                    self.effects.shake(6.0, 250.0);
+                }
+            }
+        }
 
-impl State {
-    fn new() -> Self {
-        Self {
-            food: Food::new(),
-            snake: Snake::new(280, 80),
-            frame: 0,
-            timer: 0.0,
+        if let StepEvent::Died(_) = event {
+            self.dying_ms = 0.0;
+            self.game_mode = GameMode::Dying;
         }
     }
 
-    fn play(&mut self, ctx: &mut BTerm) {
+    fn dying(&mut self, ctx: &mut BTerm) {
         ctx.set_active_console(1);
         ctx.cls();
-        ctx.print(1, 1, "Snake Game!");
-        ctx.printer(
-            70,
+        self.hud.render(
+            ctx,
             1,
-            &format!("#[pink]FPS: #[]{}", ctx.fps),
-            TextAlign::Left,
-            None,
+            &self.game,
+            self.menu.high_scores.best(),
+            self.time_alive_ms,
         );
 
         ctx.set_active_console(0);
         ctx.cls();
+        self.effects.update(ctx.frame_time_ms);
+        self.render_playfield(ctx);
 
-        if let Some(key) = ctx.key {
-            match key {
-                VirtualKeyCode::W => self.snake.change_direction(Direction::Up),
-                VirtualKeyCode::S => self.snake.change_direction(Direction::Down),
-                VirtualKeyCode::A => self.snake.change_direction(Direction::Left),
-                VirtualKeyCode::D => self.snake.change_direction(Direction::Right),
-                VirtualKeyCode::Q => ctx.quitting = true,
-                _ => {}
-            }
+        self.dying_ms += ctx.frame_time_ms;
+        if self.dying_ms > DEATH_LINGER_MS {
+            self.game_over();
         }
+    }
 
-        self.timer += ctx.frame_time_ms;
+    /// Draws the current game and its particles, shaken as need be, on
+    /// the sprite console.
+    fn render_playfield(&self, ctx: &mut BTerm) {
+        let board = (self.game.config.width, self.game.config.height);
+        let layout = Layout::fit(self.window, board).shifted(self.effects.shake_offset());
+        let animations = Animations {
+            clock_ms: self.time_alive_ms,
+            dying_ms: matches!(self.game_mode, GameMode::Dying).then_some(self.dying_ms),
+            lively: !self.menu.settings.reduced_motion,
+        };
+        render_board(
+            ctx,
+            &self.game,
+            &layout,
+            &self.snake_tiles,
+            &self.menu.settings,
+            &animations,
+        );
+        self.effects.render(ctx, &layout);
+    }
 
-        if self.timer > 66.0 {
-            self.timer = 0.0;
-            self.frame += 1;
-
-            if rects_overlap(
-                self.snake.snake_parts[0].x,
-                self.snake.snake_parts[0].y,
-                SPRITE_TILE_SIZE,
-                SPRITE_TILE_SIZE,
-                self.food.pos_x,
-                self.food.pos_y,
-                SPRITE_TILE_SIZE,
-                SPRITE_TILE_SIZE,
-            ) {
-                self.food = Food::new();
-                self.snake.grow();
-            }
-            // snake move
-            self.snake.slither();
+    fn watch_replay(&mut self, ctx: &mut BTerm) {
+        let Some(player) = self.replay_player.as_mut() else {
+            self.game_mode = GameMode::Menu;
+            return;
+        };
+
+        if !player.handle_key(ctx.key) {
+            self.replay_player = None;
+            self.game_mode = GameMode::Menu;
+            return;
         }
-        self.food.render(ctx);
-        self.snake.render(ctx);
+        player.update(ctx.frame_time_ms);
+
+        ctx.set_active_console(1);
+        ctx.cls();
+        let (_, rows) = layout::hud_size(self.window);
+        ctx.print(1, 1, format!("Score: {}", player.game.score));
+        ctx.print(1, rows as i32 - 2, player.status_line());
+        if let Some(death) = player.game.death {
+            ctx.print_color_centered(rows as i32 / 2, RED, BLACK, death.cause.describe());
+        }
+
+        ctx.set_active_console(0);
+        ctx.cls();
+        let board = (player.game.config.width, player.game.config.height);
+        render_board(
+            ctx,
+            &player.game,
+            &Layout::fit(self.window, board),
+            &self.snake_tiles,
+            &self.menu.settings,
+            &Animations::still(),
+        );
     }
 }
 
 impl GameState for State {
     fn tick(&mut self, ctx: &mut BTerm) {
-        self.play(ctx);
+        let window = (ctx.width_pixels, ctx.height_pixels);
+        if window != self.window {
+            self.window = window;
+            layout::resize_consoles(ctx, window);
+        }
+
+        match self.game_mode {
+            GameMode::Menu => self.main_menu(ctx),
+            GameMode::Playing => self.play(ctx),
+            GameMode::Dying => self.dying(ctx),
+            GameMode::Replay => self.watch_replay(ctx),
+        }
     }
 }
 
-embedded_resource!(NYAN_CAT, "../resources/texture.png");
+embedded_resource!(SNAKE_TEXTURE, "../resources/texture.png");
+embedded_resource!(TERMINAL_FONT, "../resources/terminal8x8.png");
+/// Where each sprite sits on the texture, as written by the packer.
+const TEXTURE_SHEET: &str = include_str!("../resources/texture.json");
 
-struct SnakePartSpriteLocation {
-    index: usize,
-    pos_y: i32,
-}
+const TEXTURE: Asset = Asset {
+    name: "texture.png",
+    bytes: SNAKE_TEXTURE,
+};
+const FONT: Asset = Asset {
+    name: "terminal8x8.png",
+    bytes: TERMINAL_FONT,
+};
 
 struct SnakeParts {
-    tail_up: SnakePartSpriteLocation,
-    tail_right: SnakePartSpriteLocation,
-    tail_left: SnakePartSpriteLocation,
-    tail_down: SnakePartSpriteLocation,
-    head_up: SnakePartSpriteLocation,
-    head_right: SnakePartSpriteLocation,
-    head_left: SnakePartSpriteLocation,
-    head_down: SnakePartSpriteLocation,
-    body_vertical: SnakePartSpriteLocation,
-    body_topright: SnakePartSpriteLocation,
-    body_topleft: SnakePartSpriteLocation,
-    body_horizontal: SnakePartSpriteLocation,
-    body_bottomright: SnakePartSpriteLocation,
-    body_bottomleft: SnakePartSpriteLocation,
+    tail_up: usize,
+    tail_right: usize,
+    tail_left: usize,
+    tail_down: usize,
+    head_up: usize,
+    head_right: usize,
+    head_left: usize,
+    head_down: usize,
+    body_vertical: usize,
+    body_topright: usize,
+    body_topleft: usize,
+    body_horizontal: usize,
+    body_bottomright: usize,
+    body_bottomleft: usize,
+    apple: usize,
+    /// Sizes and pivots of all of the above.
+    sprites: SpriteIndex,
 }
 
 impl SnakeParts {
-    fn new() -> Self {
+    fn new(sprites: &SpriteIndex) -> Self {
         Self {
-            tail_up: SnakePartSpriteLocation { index: 0, pos_y: 0 },
-            tail_right: SnakePartSpriteLocation {
-                index: 1,
-                pos_y: 40,
-            },
-            tail_left: SnakePartSpriteLocation {
-                index: 2,
-                pos_y: 80,
-            },
-            tail_down: SnakePartSpriteLocation {
-                index: 3,
-                pos_y: 120,
-            },
-            head_up: SnakePartSpriteLocation {
-                index: 4,
-                pos_y: 160,
-            },
-            head_right: SnakePartSpriteLocation {
-                index: 5,
-                pos_y: 200,
-            },
-            head_left: SnakePartSpriteLocation {
-                index: 6,
-                pos_y: 240,
-            },
-            head_down: SnakePartSpriteLocation {
-                index: 7,
-                pos_y: 280,
-            },
-            body_vertical: SnakePartSpriteLocation {
-                index: 8,
-                pos_y: 320,
-            },
-            body_topright: SnakePartSpriteLocation {
-                index: 9,
-                pos_y: 360,
-            },
-            body_topleft: SnakePartSpriteLocation {
-                index: 10,
-                pos_y: 400,
-            },
-            body_horizontal: SnakePartSpriteLocation {
-                index: 11,
-                pos_y: 440,
-            },
-            body_bottomright: SnakePartSpriteLocation {
-                index: 12,
-                pos_y: 480,
-            },
-            body_bottomleft: SnakePartSpriteLocation {
-                index: 13,
-                pos_y: 520,
-            },
+            sprites: sprites.clone(),
+            tail_up: sprites.index("tail_up"),
+            tail_right: sprites.index("tail_right"),
+            tail_left: sprites.index("tail_left"),
+            tail_down: sprites.index("tail_down"),
+            head_up: sprites.index("head_up"),
+            head_right: sprites.index("head_right"),
+            head_left: sprites.index("head_left"),
+            head_down: sprites.index("head_down"),
+            body_vertical: sprites.index("body_vertical"),
+            body_topright: sprites.index("body_topright"),
+            body_topleft: sprites.index("body_topleft"),
+            body_horizontal: sprites.index("body_horizontal"),
+            body_bottomright: sprites.index("body_bottomright"),
+            body_bottomleft: sprites.index("body_bottomleft"),
+            apple: sprites.index("apple"),
         }
     }
+
+    /// Where to draw `sprite` for the board cell `cell`.
+    fn place(&self, layout: &Layout, cell: &Point, sprite: usize) -> Rect {
+        layout.sprite_rect(cell, self.sprites.frame(sprite))
+    }
 }
 
 fn main() -> BError {
-    link_resource!(NYAN_CAT, "resources/texture.png");
+    // `--replay <file>` starts straight into playback of a shared run
+    let args: Vec<String> = std::env::args().collect();
+    let replay = match args.iter().position(|arg| arg == "--replay") {
+        Some(index) => {
+            let path = args.get(index + 1).ok_or("--replay needs a file")?;
+            Some(Replay::load(path)?)
+        }
+        None => None,
+    };
+    // `--assets <dir>` swaps in any same-named images from a mod directory
+    let override_dir = match args.iter().position(|arg| arg == "--assets") {
+        Some(index) => Some(Path::new(
+            args.get(index + 1).ok_or("--assets needs a directory")?,
+        )),
+        None => None,
+    };
+
+    let fullscreen = args.iter().any(|arg| arg == "--fullscreen");
+
+    if let Err(err) = assets::link(&[TEXTURE, FONT], override_dir) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
     println!("snake with sprites");
 
-    let snake_tile_map = SnakeParts::new();
+    let sheet = match aseprite::import(TEXTURE_SHEET, &TEXTURE.path()) {
+        Ok(sheet) => sheet,
+        Err(err) => {
+            eprintln!("texture.json: {}", err);
+            std::process::exit(1);
+        }
+    };
 
     let context = BTermBuilder::new()
         .with_title("Sprite Snake")
         .with_dimensions(SCREEN_WIDTH / TILE_W, SCREEN_HEIGHT / TILE_H)
         .with_tile_dimensions(TILE_W, TILE_H)
-        .with_font("terminal8x8.png", 8, 8)
+        .with_font(FONT.name, 8, 8)
         .with_sprite_console(SCREEN_WIDTH, SCREEN_HEIGHT, 0)
-        .with_simple_console_no_bg(80, 50, "terminal8x8.png")
-        .with_sprite_sheet(
-            SpriteSheet::new("resources/texture.png")
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.tail_up.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.tail_right.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.tail_left.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.tail_down.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.head_up.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.head_right.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.head_left.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.head_down.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.body_vertical.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.body_topright.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.body_topleft.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.body_horizontal.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.body_bottomright.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(
-                    0,
-                    snake_tile_map.body_bottomleft.pos_y,
-                    SPRITE_TILE_SIZE,
-                    SPRITE_TILE_SIZE,
-                ))
-                .add_sprite(Rect::with_size(0, 560, SPRITE_TILE_SIZE, SPRITE_TILE_SIZE)),
+        .with_simple_console_no_bg(
+            SCREEN_WIDTH / HUD_GLYPH,
+            SCREEN_HEIGHT / HUD_GLYPH,
+            FONT.name,
         )
+        .with_sprite_sheet(sheet.sheet)
+        .with_fullscreen(fullscreen)
         .with_vsync(false)
         .build()?;
 
-    main_loop(context, State::new())
+    let mut state = State::new(SnakeParts::new(&sheet.sprites));
+    if let Some(replay) = replay {
+        state.watch(replay);
+    }
+
+    main_loop(context, state)
 }
@@ -0,0 +1,140 @@
+use bracket_lib::prelude::*;
+
+use super::{direction_towards, BoardMap, Controller};
+use crate::{Direction, Game, Snake, SnakeId};
+
+/// Greedy pathing: takes the shortest A* path to the food, but only when
+/// the snake could still reach its own tail after eating it. Otherwise it
+/// chases its tail, and as a last resort heads for the biggest open area.
+pub struct AStarController;
+
+impl AStarController {
+    fn is_safe_path(game: &Game, snake: &Snake, path: &[Point]) -> bool {
+        // where the body ends up once the path is walked and the food eaten
+        let mut body = snake.body.clone();
+        for (i, cell) in path.iter().enumerate() {
+            body.insert(0, *cell);
+            if i + 1 < path.len() {
+                body.pop();
+            }
+        }
+
+        let mut map = BoardMap::from_game(game);
+        for cell in &snake.body {
+            map.set_blocked(*cell, false);
+        }
+        for cell in &body[..body.len() - 1] {
+            map.set_blocked(*cell, true);
+        }
+
+        let head = body[0];
+        let tail = body[body.len() - 1];
+        map.set_blocked(head, false);
+        map.path(head, tail).is_some()
+    }
+
+    fn roomiest_direction(game: &Game, snake: &Snake, map: &BoardMap) -> Direction {
+        Direction::ALL
+            .into_iter()
+            .filter(|d| *d != snake.direction.opposite())
+            .max_by_key(|d| map.reachable_area(snake.head() + d.delta()))
+            .unwrap_or(snake.direction)
+    }
+}
+
+impl Controller for AStarController {
+    fn name(&self) -> &'static str {
+        "a-star"
+    }
+
+    fn next_direction(&mut self, game: &Game, id: SnakeId) -> Direction {
+        let Some(snake) = game.snake_by_id(id) else {
+            return Direction::Right;
+        };
+        let map = BoardMap::from_game(game);
+        let head = snake.head();
+        // a two cell snake's tail is right behind its head, so a path can
+        // start by turning back, which the game won't do
+        let towards = |step: Point| {
+            direction_towards(head, step).filter(|d| *d != snake.direction.opposite())
+        };
+
+        if let Some(food) = game.nearest_food(head) {
+            if let Some(path) = map.path(head, food.cell) {
+                if Self::is_safe_path(game, snake, &path) {
+                    if let Some(direction) = towards(path[0]) {
+                        return direction;
+                    }
+                }
+            }
+        }
+
+        let tail = snake.body[snake.length() - 1];
+        if snake.length() > 2 {
+            if let Some(step) = map.path(head, tail).and_then(|path| path.first().copied()) {
+                if let Some(direction) = towards(step) {
+                    return direction;
+                }
+            }
+        }
+
+        Self::roomiest_direction(game, snake, &map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameConfig, SpawnPolicy};
+
+    fn config(size: i32) -> GameConfig {
+        GameConfig {
+            width: size,
+            height: size,
+            initial_length: 2,
+            opponent: false,
+            power_ups: false,
+            spawn: SpawnPolicy::CLASSIC,
+        }
+    }
+
+    #[test]
+    fn never_turns_into_itself_while_a_safe_move_exists() {
+        for seed in 0..10 {
+            // small enough that the snake soon fills much of it
+            let mut game = Game::new(config(6), seed);
+            while !game.is_over() && !game.is_complete() && game.ticks < 500 {
+                let map = BoardMap::from_game(&game);
+                let head = game.snake.head();
+                let direction = AStarController.next_direction(&game, SnakeId::Player);
+                let safe = Direction::ALL.iter().any(|d| map.is_free(head + d.delta()));
+                if safe {
+                    // the game ignores a reversal and carries straight on
+                    assert_ne!(direction, game.snake.direction.opposite());
+                    assert!(
+                        map.is_free(head + direction.delta()),
+                        "seed {} tick {}: {:?} from {:?}",
+                        seed,
+                        game.ticks,
+                        direction,
+                        head
+                    );
+                }
+                game.step(Some(direction));
+            }
+        }
+    }
+
+    #[test]
+    fn reaches_food_on_an_open_board() {
+        for seed in 0..10 {
+            let mut game = Game::new(config(16), seed);
+            while game.foods_eaten < 10 {
+                assert!(!game.is_over(), "seed {}: died", seed);
+                assert!(game.ticks < 10 * 40, "seed {}: too slow", seed);
+                let direction = AStarController.next_direction(&game, SnakeId::Player);
+                game.step(Some(direction));
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use bracket_lib::prelude::*;
+
+use super::{direction_towards, AStarController, BoardMap, Controller};
+use crate::{Direction, Game, SnakeId};
+
+/// Extra cells kept between head and tail when taking a shortcut, so a
+/// meal on the way doesn't close the gap.
+const SHORTCUT_BUFFER: i32 = 3;
+
+/// Follows a Hamiltonian cycle through every cell of the board, which
+/// can never trap the snake, and cuts across it towards the food while
+/// the snake is short enough for that to be safe.
+///
+/// Needs an even width or height for the cycle to exist; on other boards
+/// it plays like [`AStarController`].
+pub struct HamiltonianController {
+    dimensions: Point,
+    /// Position of each cell along the cycle, indexed like the board.
+    order: Vec<i32>,
+    fallback: AStarController,
+}
+
+impl HamiltonianController {
+    pub fn new() -> Self {
+        Self {
+            dimensions: Point::zero(),
+            order: Vec::new(),
+            fallback: AStarController,
+        }
+    }
+
+    fn build_cycle(&mut self, width: i32, height: i32) {
+        self.dimensions = Point::new(width, height);
+        self.order = match cycle(width, height) {
+            Some(cells) => {
+                let mut order = vec![0; (width * height) as usize];
+                for (position, cell) in cells.iter().enumerate() {
+                    order[(cell.y * width + cell.x) as usize] = position as i32;
+                }
+                order
+            }
+            None => Vec::new(),
+        };
+    }
+
+    fn position(&self, cell: Point) -> i32 {
+        self.order[(cell.y * self.dimensions.x + cell.x) as usize]
+    }
+
+    /// How many steps along the cycle it takes to get from `a` to `b`.
+    fn distance(&self, a: Point, b: Point) -> i32 {
+        let size = self.order.len() as i32;
+        (self.position(b) - self.position(a) + size) % size
+    }
+}
+
+impl Controller for HamiltonianController {
+    fn name(&self) -> &'static str {
+        "hamiltonian"
+    }
+
+    fn next_direction(&mut self, game: &Game, id: SnakeId) -> Direction {
+        let dimensions = Point::new(game.config.width, game.config.height);
+        if self.dimensions != dimensions {
+            self.build_cycle(dimensions.x, dimensions.y);
+        }
+        let Some(snake) = game.snake_by_id(id) else {
+            return Direction::Right;
+        };
+        if self.order.is_empty() {
+            return self.fallback.next_direction(game, id);
+        }
+
+        let map = BoardMap::from_game(game);
+        let head = snake.head();
+        let tail = snake.body[snake.length() - 1];
+        let size = self.order.len() as i32;
+
+        // the body only stays in cycle order while there is room to spare
+        let mut allowed = if (snake.length() as i32) < size / 2 {
+            self.distance(head, tail) - SHORTCUT_BUFFER
+        } else {
+            1
+        };
+        if let Some(to_food) = game
+            .foods
+            .iter()
+            .map(|food| self.distance(head, food.cell))
+            .min()
+        {
+            if to_food < self.distance(head, tail) {
+                allowed = allowed.min(to_food);
+            }
+        }
+        let allowed = allowed.max(1);
+
+        let best = Direction::ALL
+            .into_iter()
+            .map(|d| (d, head + d.delta()))
+            .filter(|(d, next)| *d != snake.direction.opposite() || snake.length() == 1)
+            .filter(|(_, next)| map.is_free(*next))
+            .filter(|(_, next)| self.distance(head, *next) <= allowed)
+            .max_by_key(|(_, next)| self.distance(head, *next));
+
+        match best {
+            Some((direction, _)) => direction,
+            // off the cycle, e.g. at the start or when an opponent is in the way
+            None => self.fallback.next_direction(game, id),
+        }
+    }
+}
+
+impl Default for HamiltonianController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cells of a Hamiltonian cycle over the board in visiting order: down
+/// and up the columns, skipping the top row, which is the way back.
+fn cycle(width: i32, height: i32) -> Option<Vec<Point>> {
+    // a cycle through an odd number of cells can't alternate colours
+    // like a chessboard, so it can't exist
+    if width < 2 || height < 2 || (width % 2 != 0 && height % 2 != 0) {
+        return None;
+    }
+    if width % 2 != 0 {
+        let transposed = cycle(height, width)?;
+        return Some(transposed.iter().map(|p| Point::new(p.y, p.x)).collect());
+    }
+
+    let mut cells = Vec::with_capacity((width * height) as usize);
+    for x in 0..width {
+        if x % 2 == 0 {
+            cells.extend((1..height).map(|y| Point::new(x, y)));
+        } else {
+            cells.extend((1..height).rev().map(|y| Point::new(x, y)));
+        }
+    }
+    cells.extend((0..width).rev().map(|x| Point::new(x, 0)));
+
+    Some(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks `cells` visits every cell once, one step at a time, and
+    /// ends next to where it started.
+    fn assert_hamiltonian(cells: &[Point], width: i32, height: i32) {
+        assert_eq!(cells.len(), (width * height) as usize);
+        let mut seen = vec![false; cells.len()];
+        for cell in cells {
+            assert!(cell.x >= 0 && cell.x < width && cell.y >= 0 && cell.y < height);
+            let index = (cell.y * width + cell.x) as usize;
+            assert!(!seen[index], "{:?} visited twice", cell);
+            seen[index] = true;
+        }
+        for (a, b) in cells.iter().zip(cells.iter().cycle().skip(1)) {
+            let step = *b - *a;
+            assert_eq!(step.x.abs() + step.y.abs(), 1, "{:?} to {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn even_board_has_a_cycle() {
+        let cells = cycle(6, 4).unwrap();
+        assert_hamiltonian(&cells, 6, 4);
+    }
+
+    #[test]
+    fn odd_width_is_transposed() {
+        let cells = cycle(5, 4).unwrap();
+        assert_hamiltonian(&cells, 5, 4);
+    }
+
+    #[test]
+    fn odd_board_has_no_cycle() {
+        assert!(cycle(9, 9).is_none());
+        assert!(cycle(3, 5).is_none());
+    }
+}
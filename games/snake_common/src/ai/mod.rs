@@ -0,0 +1,213 @@
+use bracket_lib::prelude::*;
+
+use crate::{Direction, Game, Snake, SnakeId};
+
+mod astar;
+mod hamiltonian;
+
+pub use astar::AStarController;
+pub use hamiltonian::HamiltonianController;
+
+/// Picks a direction for one of the snakes every tick.
+pub trait Controller {
+    fn name(&self) -> &'static str;
+
+    fn next_direction(&mut self, game: &Game, id: SnakeId) -> Direction;
+}
+
+/// The board as seen by a pathfinder: which cells are free to walk on.
+pub struct BoardMap {
+    width: i32,
+    height: i32,
+    blocked: Vec<bool>,
+}
+
+impl BoardMap {
+    /// Blocks every snake cell that will still be occupied after the next
+    /// move, which leaves the tails free since they move out of the way.
+    pub fn from_game(game: &Game) -> Self {
+        let mut map = Self {
+            width: game.config.width,
+            height: game.config.height,
+            blocked: vec![false; (game.config.width * game.config.height) as usize],
+        };
+        map.block_snake(&game.snake);
+        if let Some(opponent) = &game.opponent {
+            map.block_snake(opponent);
+        }
+        map
+    }
+
+    fn block_snake(&mut self, snake: &Snake) {
+        for cell in &snake.body[..snake.length() - 1] {
+            self.set_blocked(*cell, true);
+        }
+    }
+
+    pub fn set_blocked(&mut self, cell: Point, blocked: bool) {
+        if self.in_bounds(cell) {
+            let idx = self.point2d_to_index(cell);
+            self.blocked[idx] = blocked;
+        }
+    }
+
+    pub fn is_free(&self, cell: Point) -> bool {
+        self.in_bounds(cell) && !self.blocked[self.point2d_to_index(cell)]
+    }
+
+    /// Number of free cells reachable from `start`, including `start`.
+    pub fn reachable_area(&self, start: Point) -> usize {
+        if !self.is_free(start) {
+            return 0;
+        }
+
+        let mut seen = vec![false; self.blocked.len()];
+        let mut open = vec![start];
+        let mut area = 0;
+        seen[self.point2d_to_index(start)] = true;
+
+        while let Some(cell) = open.pop() {
+            area += 1;
+            for direction in Direction::ALL {
+                let next = cell + direction.delta();
+                if self.is_free(next) && !seen[self.point2d_to_index(next)] {
+                    seen[self.point2d_to_index(next)] = true;
+                    open.push(next);
+                }
+            }
+        }
+
+        area
+    }
+
+    /// Shortest path from `start` to `end`, excluding `start`.
+    pub fn path(&self, start: Point, end: Point) -> Option<Vec<Point>> {
+        let path = a_star_search(
+            self.point2d_to_index(start),
+            self.point2d_to_index(end),
+            self,
+        );
+        if !path.success {
+            return None;
+        }
+
+        Some(
+            path.steps[1..]
+                .iter()
+                .map(|idx| self.index_to_point2d(*idx))
+                .collect(),
+        )
+    }
+}
+
+impl BaseMap for BoardMap {
+    fn is_opaque(&self, idx: usize) -> bool {
+        self.blocked[idx]
+    }
+
+    fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
+        let cell = self.index_to_point2d(idx);
+        Direction::ALL
+            .iter()
+            .map(|direction| cell + direction.delta())
+            .filter(|next| self.is_free(*next))
+            .map(|next| (self.point2d_to_index(next), 1.0))
+            .collect()
+    }
+
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        DistanceAlg::Manhattan.distance2d(self.index_to_point2d(idx1), self.index_to_point2d(idx2))
+    }
+}
+
+impl Algorithm2D for BoardMap {
+    fn dimensions(&self) -> Point {
+        Point::new(self.width, self.height)
+    }
+}
+
+pub fn direction_towards(from: Point, to: Point) -> Option<Direction> {
+    Direction::ALL.into_iter().find(|d| from + d.delta() == to)
+}
+
+/// Every strategy, for picking one by name.
+pub fn controllers() -> Vec<Box<dyn Controller>> {
+    vec![
+        Box::new(AStarController),
+        Box::new(HamiltonianController::new()),
+    ]
+}
+
+/// The move of the CPU opponent for this tick, if the game has one.
+pub fn opponent_move(game: &Game) -> Option<Direction> {
+    game.opponent
+        .as_ref()
+        .map(|_| AStarController.next_direction(game, SnakeId::Opponent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameConfig, SpawnPolicy};
+
+    /// A 10x10 board with the snake's head at (2, 5), heading right, and
+    /// its body at (1, 5) and (0, 5).
+    fn game() -> Game {
+        Game::new(
+            GameConfig {
+                width: 10,
+                height: 10,
+                initial_length: 3,
+                opponent: false,
+                power_ups: false,
+                spawn: SpawnPolicy::CLASSIC,
+            },
+            1,
+        )
+    }
+
+    fn exits(map: &BoardMap, cell: Point) -> Vec<Point> {
+        let mut cells: Vec<Point> = map
+            .get_available_exits(map.point2d_to_index(cell))
+            .iter()
+            .map(|(idx, _)| map.index_to_point2d(*idx))
+            .collect();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        cells
+    }
+
+    #[test]
+    fn exits_leave_out_the_body_and_walls() {
+        let map = BoardMap::from_game(&game());
+        assert_eq!(
+            exits(&map, Point::new(1, 4)),
+            vec![Point::new(1, 3), Point::new(0, 4), Point::new(2, 4)]
+        );
+        assert_eq!(
+            exits(&map, Point::new(0, 0)),
+            vec![Point::new(1, 0), Point::new(0, 1)]
+        );
+        assert_eq!(
+            exits(&map, Point::new(9, 9)),
+            vec![Point::new(9, 8), Point::new(8, 9)]
+        );
+    }
+
+    #[test]
+    fn the_tail_is_free_since_it_moves_away() {
+        let map = BoardMap::from_game(&game());
+        assert!(!map.is_free(Point::new(2, 5)));
+        assert!(!map.is_free(Point::new(1, 5)));
+        assert!(map.is_free(Point::new(0, 5)));
+        assert!(exits(&map, Point::new(0, 4)).contains(&Point::new(0, 5)));
+    }
+
+    #[test]
+    fn paths_go_around_the_body() {
+        let map = BoardMap::from_game(&game());
+        let path = map.path(Point::new(1, 4), Point::new(1, 6)).unwrap();
+        assert_eq!(path.len(), 4);
+        assert!(path.iter().all(|cell| map.is_free(*cell)));
+        assert_eq!(path.last(), Some(&Point::new(1, 6)));
+    }
+}
@@ -0,0 +1,403 @@
+//! Reads the JSON Aseprite writes next to an exported sprite sheet and
+//! turns it into a [`SpriteSheet`], sprite names and animation [`Clips`],
+//! one clip per tag.
+//!
+//! Both of Aseprite's layouts for the frame list are understood: a hash
+//! keyed by frame name (also what packers like free-tex-packer write)
+//! and an array. Sprites are named after their frame, less the file
+//! extension, so `dood 0.aseprite` becomes `dood 0`. A frame's `pivot`,
+//! which packers like free-tex-packer add, becomes its sprite's pivot;
+//! frames without one are pivoted on their centre. Pivots are measured
+//! on the untrimmed frame, so a trimmed frame still lands where the full
+//! frame would have.
+
+use std::path::Path;
+
+use bracket_lib::prelude::{PointF, Rect, SpriteSheet};
+use serde::Deserialize;
+use serde_json::Map;
+
+use crate::clips::{Clip, Clips, Frame, Playback};
+use crate::sheet::{Sheet, SpriteFrame, SpriteIndex};
+
+/// Aseprite's own default, used for frames that don't say.
+const DEFAULT_FRAME_MS: f32 = 100.0;
+
+pub struct Import {
+    pub sheet: SpriteSheet,
+    pub sprites: SpriteIndex,
+    pub clips: Clips,
+}
+
+#[derive(Deserialize)]
+struct Export {
+    frames: Frames,
+    meta: Meta,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Frames {
+    Hash(Map<String, serde_json::Value>),
+    Array(Vec<NamedFrame>),
+}
+
+#[derive(Deserialize)]
+struct NamedFrame {
+    filename: String,
+    #[serde(flatten)]
+    frame: ExportFrame,
+}
+
+#[derive(Deserialize)]
+struct ExportFrame {
+    frame: Bounds,
+    duration: Option<f32>,
+    pivot: Option<Pivot>,
+    /// Whether blank borders were cut off, leaving `frame` as the part of
+    /// the untrimmed `sourceSize` at `spriteSourceSize`.
+    #[serde(default)]
+    trimmed: bool,
+    #[serde(rename = "spriteSourceSize")]
+    sprite_source_size: Option<Bounds>,
+    #[serde(rename = "sourceSize")]
+    source_size: Option<Size>,
+}
+
+impl ExportFrame {
+    /// The pivot as a fraction of the frame on the texture, moved from the
+    /// untrimmed frame onto the trimmed one.
+    fn pivot(&self) -> Result<PointF, String> {
+        let pivot = self
+            .pivot
+            .as_ref()
+            .map_or(SpriteFrame::CENTRE, |p| PointF::new(p.x, p.y));
+        if !self.trimmed {
+            return Ok(pivot);
+        }
+        let (Some(trim), Some(source)) = (&self.sprite_source_size, &self.source_size) else {
+            return Err("trimmed without spriteSourceSize and sourceSize".to_string());
+        };
+        let b = &self.frame;
+        Ok(PointF::new(
+            (pivot.x * source.w as f32 - trim.x as f32) / b.w.max(1) as f32,
+            (pivot.y * source.h as f32 - trim.y as f32) / b.h.max(1) as f32,
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct Pivot {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+struct Bounds {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+}
+
+#[derive(Deserialize)]
+struct Meta {
+    size: Size,
+    #[serde(default, rename = "frameTags")]
+    frame_tags: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+struct Size {
+    w: i32,
+    h: i32,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+    /// How many times to play it, as a string. Loops forever if absent
+    /// or 0.
+    repeat: Option<String>,
+}
+
+/// Builds a sheet for the texture at `texture` from Aseprite's JSON.
+pub fn import(json: &str, texture: &str) -> Result<Import, String> {
+    let export: Export = serde_json::from_str(json).map_err(|err| err.to_string())?;
+
+    let frames = match export.frames {
+        Frames::Hash(map) => map
+            .into_iter()
+            .map(|(name, value)| {
+                let frame = serde_json::from_value(value)
+                    .map_err(|err| format!("frame '{}': {}", name, err))?;
+                Ok((name, frame))
+            })
+            .collect::<Result<Vec<(String, ExportFrame)>, String>>()?,
+        Frames::Array(list) => list.into_iter().map(|f| (f.filename, f.frame)).collect(),
+    };
+
+    // Aseprite measures from the top of the image, bracket from the bottom
+    let height = export.meta.size.h;
+    let sheet = frames
+        .iter()
+        .try_fold(Sheet::new(texture), |sheet, (name, f)| {
+            let pivot = f
+                .pivot()
+                .map_err(|err| format!("frame '{}': {}", name, err))?;
+            let name = Path::new(name)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(name);
+            let b = &f.frame;
+            Ok::<_, String>(sheet.region_with_pivot(
+                name,
+                Rect::with_size(b.x, height - b.y - b.h, b.w, b.h),
+                pivot,
+            ))
+        })?;
+    let (sheet, sprites) = sheet.build();
+
+    let clips = export
+        .meta
+        .frame_tags
+        .iter()
+        .map(|tag| tag_clip(tag, &frames))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Import {
+        sheet,
+        sprites,
+        clips: Clips::new(clips),
+    })
+}
+
+fn tag_clip(tag: &Tag, frames: &[(String, ExportFrame)]) -> Result<Clip, String> {
+    if tag.from > tag.to || tag.to >= frames.len() {
+        return Err(format!(
+            "tag '{}' covers frames {}..={} of {}",
+            tag.name,
+            tag.from,
+            tag.to,
+            frames.len()
+        ));
+    }
+
+    let mut cycle: Vec<Frame> = (tag.from..=tag.to)
+        .map(|sprite| Frame {
+            sprite,
+            duration_ms: frames[sprite].1.duration.unwrap_or(DEFAULT_FRAME_MS),
+        })
+        .collect();
+    let playback = match tag.direction.as_str() {
+        "" | "forward" => Playback::Loop,
+        "reverse" => {
+            cycle.reverse();
+            Playback::Loop
+        }
+        "pingpong" => Playback::PingPong,
+        "pingpong_reverse" => {
+            cycle.reverse();
+            Playback::PingPong
+        }
+        other => return Err(format!("tag '{}': unknown direction '{}'", tag.name, other)),
+    };
+
+    let times: usize = match &tag.repeat {
+        Some(repeat) => repeat
+            .parse()
+            .map_err(|_| format!("tag '{}': bad repeat '{}'", tag.name, repeat))?,
+        None => 0,
+    };
+    if times == 0 {
+        return Ok(Clip {
+            name: tag.name.clone(),
+            playback,
+            frames: cycle,
+        });
+    }
+
+    // a set number of repeats plays through that many times, then stops
+    let mut pass = cycle.clone();
+    if playback == Playback::PingPong && cycle.len() > 2 {
+        pass.extend(cycle[1..cycle.len() - 1].iter().rev().cloned());
+    }
+    let mut frames: Vec<Frame> = pass
+        .iter()
+        .cycle()
+        .take(pass.len() * times)
+        .cloned()
+        .collect();
+    if playback == Playback::PingPong {
+        // back where it started
+        frames.push(cycle[0].clone());
+    }
+
+    Ok(Clip {
+        name: tag.name.clone(),
+        playback: Playback::Once,
+        frames,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three 16x16 frames in a row at the top of a 48x32 texture.
+    fn frame(x: i32, extra: &str) -> String {
+        format!(
+            r#"{{ "frame": {{ "x": {}, "y": 0, "w": 16, "h": 16 }}{} }}"#,
+            x, extra
+        )
+    }
+
+    fn frames() -> [String; 3] {
+        [
+            frame(0, r#", "duration": 80"#),
+            frame(16, r#", "duration": 120, "pivot": { "x": 0.25, "y": 1.0 }"#),
+            frame(32, ""),
+        ]
+    }
+
+    fn export(frames: &str, tags: &str) -> String {
+        format!(
+            r#"{{ "frames": {}, "meta": {{ "size": {{ "w": 48, "h": 32 }}, "frameTags": [{}] }} }}"#,
+            frames, tags
+        )
+    }
+
+    fn hash(tags: &str) -> String {
+        let [a, b, c] = frames();
+        let frames = format!(
+            r#"{{ "dood 0.aseprite": {}, "dood 1.aseprite": {}, "dood 2.aseprite": {} }}"#,
+            a, b, c
+        );
+        export(&frames, tags)
+    }
+
+    fn array(tags: &str) -> String {
+        let frames: Vec<String> = frames()
+            .iter()
+            .enumerate()
+            .map(|(i, f)| format!(r#"{{ "filename": "dood {}.aseprite", {}"#, i, &f[1..]))
+            .collect();
+        export(&format!("[{}]", frames.join(", ")), tags)
+    }
+
+    fn tag(direction: &str, from: usize, to: usize) -> String {
+        format!(
+            r#"{{ "name": "{}", "from": {}, "to": {}, "direction": "{}" }}"#,
+            direction, from, to, direction
+        )
+    }
+
+    /// Sprites and durations of a clip's frames.
+    fn timeline(clip: &Clip) -> Vec<(usize, f32)> {
+        clip.frames
+            .iter()
+            .map(|f| (f.sprite, f.duration_ms))
+            .collect()
+    }
+
+    #[test]
+    fn reads_hash_and_array_frames_alike() {
+        for json in [hash(""), array("")] {
+            let import = import(&json, "dood.png").unwrap();
+            let sprites = &import.sprites;
+            for (i, name) in ["dood 0", "dood 1", "dood 2"].iter().enumerate() {
+                assert_eq!(sprites.index(name), i);
+                let rect = import.sheet.sprites[i].sheet_location;
+                assert_eq!(
+                    (rect.x1, rect.width(), rect.height()),
+                    (16 * i as i32, 16, 16)
+                );
+            }
+            // flipped to bracket's bottom-up rows
+            let rect = import.sheet.sprites[1].sheet_location;
+            assert_eq!((rect.x1, rect.y1), (16, 16));
+        }
+    }
+
+    #[test]
+    fn reads_pivots() {
+        let import = import(&hash(""), "dood.png").unwrap();
+        assert_eq!(import.sprites.frame(1).pivot, PointF::new(0.25, 1.0));
+        assert_eq!(import.sprites.frame(0).pivot, SpriteFrame::CENTRE);
+    }
+
+    #[test]
+    fn reads_durations_and_directions() {
+        let tags = [
+            tag("forward", 0, 2),
+            tag("reverse", 0, 2),
+            tag("pingpong", 0, 2),
+        ]
+        .join(", ");
+        let import = import(&array(&tags), "dood.png").unwrap();
+        let clips = &import.clips;
+
+        let forward = clips.get(clips.find("forward").unwrap());
+        assert_eq!(forward.playback, Playback::Loop);
+        assert_eq!(
+            timeline(forward),
+            [(0, 80.0), (1, 120.0), (2, DEFAULT_FRAME_MS)]
+        );
+
+        let reverse = clips.get(clips.find("reverse").unwrap());
+        assert_eq!(reverse.playback, Playback::Loop);
+        assert_eq!(
+            timeline(reverse),
+            [(2, DEFAULT_FRAME_MS), (1, 120.0), (0, 80.0)]
+        );
+
+        let pingpong = clips.get(clips.find("pingpong").unwrap());
+        assert_eq!(pingpong.playback, Playback::PingPong);
+        assert_eq!(
+            timeline(pingpong),
+            [(0, 80.0), (1, 120.0), (2, DEFAULT_FRAME_MS)]
+        );
+        // back down through the middle frame without repeating the ends
+        assert_eq!(pingpong.sprite_at(80.0 + 120.0 + DEFAULT_FRAME_MS), 1);
+    }
+
+    #[test]
+    fn rejects_tags_past_the_frames() {
+        let err = import(&hash(&tag("forward", 1, 3)), "dood.png")
+            .err()
+            .unwrap();
+        assert!(err.contains("tag 'forward'"), "{}", err);
+        assert!(import(&hash(&tag("forward", 2, 1)), "dood.png").is_err());
+    }
+
+    #[test]
+    fn moves_pivots_onto_trimmed_frames() {
+        // a 32x32 frame trimmed down to the 16x16 at (4, 16) within it
+        let trimmed = |pivot: &str| {
+            let frame = format!(
+                r#"[{{ "filename": "a", "frame": {{ "x": 0, "y": 0, "w": 16, "h": 16 }},
+                "trimmed": true, "spriteSourceSize": {{ "x": 4, "y": 16, "w": 16, "h": 16 }},
+                "sourceSize": {{ "w": 32, "h": 32 }}{} }}]"#,
+                pivot
+            );
+            import(&export(&frame, ""), "dood.png").unwrap().sprites
+        };
+        assert_eq!(trimmed("").frame(0).pivot, PointF::new(0.75, 0.0));
+        let feet = r#", "pivot": { "x": 0.5, "y": 1.0 }"#;
+        assert_eq!(trimmed(feet).frame(0).pivot, PointF::new(0.75, 1.0));
+    }
+
+    #[test]
+    fn rejects_trimmed_frames_without_their_source() {
+        let json = export(
+            r#"[{ "filename": "a", "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "trimmed": true }]"#,
+            "",
+        );
+        let err = import(&json, "dood.png").err().unwrap();
+        assert!(err.contains("trimmed"), "{}", err);
+    }
+}
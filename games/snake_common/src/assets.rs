@@ -0,0 +1,66 @@
+//! Images compiled into the game binary, so it runs from any working
+//! directory, with an optional directory of replacements for modding.
+
+use std::path::Path;
+
+use bracket_lib::prelude::EMBED;
+
+/// Prefix bracket-lib puts in front of font names when it looks them up.
+const RESOURCE_DIR: &str = "resources";
+
+/// An image embedded with `include_bytes!`, registered under
+/// `resources/<name>`, the path to use with `SpriteSheet::new` and, minus
+/// the prefix, `with_font`.
+pub struct Asset {
+    pub name: &'static str,
+    pub bytes: &'static [u8],
+}
+
+impl Asset {
+    pub fn path(&self) -> String {
+        format!("{}/{}", RESOURCE_DIR, self.name)
+    }
+}
+
+/// Registers every asset with bracket-lib. A file with the same name in
+/// `override_dir` replaces the embedded copy.
+///
+/// Each image is decoded once up front, so a missing or broken file is
+/// reported here, listing every asset that failed, rather than as a panic
+/// inside the renderer.
+pub fn link(assets: &[Asset], override_dir: Option<&Path>) -> Result<(), String> {
+    let mut failures = Vec::new();
+
+    for asset in assets {
+        let (source, bytes) = match override_dir.map(|dir| dir.join(asset.name)) {
+            Some(file) if file.exists() => match std::fs::read(&file) {
+                Ok(bytes) => (
+                    file.display().to_string(),
+                    &*Box::leak(bytes.into_boxed_slice()),
+                ),
+                Err(err) => {
+                    failures.push(format!("{}: {}", file.display(), err));
+                    continue;
+                }
+            },
+            _ => (format!("{} (embedded)", asset.name), asset.bytes),
+        };
+
+        if let Err(err) = image::load_from_memory(bytes) {
+            failures.push(format!("{}: {}", source, err));
+            continue;
+        }
+
+        EMBED.lock().add_resource(asset.path(), bytes);
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "could not load {} asset(s):\n  {}",
+            failures.len(),
+            failures.join("\n  ")
+        ))
+    }
+}
@@ -0,0 +1,165 @@
+//! Plays seeded games with every AI strategy, without a window, and
+//! prints how well each one did.
+//!
+//! ```text
+//! cargo run --release -p snake_common --bin snake_bench -- --games 20 --size 16x12
+//! ```
+
+use std::time::{Duration, Instant};
+
+use snake_common::ai::{self, Controller};
+use snake_common::{Game, GameConfig, SnakeId, SpawnPolicy, StepEvent};
+
+struct Options {
+    games: u64,
+    first_seed: u64,
+    width: i32,
+    height: i32,
+    max_steps: u32,
+    strategies: Vec<String>,
+}
+
+impl Options {
+    fn config(&self) -> GameConfig {
+        GameConfig {
+            width: self.width,
+            height: self.height,
+            initial_length: 3,
+            opponent: false,
+            power_ups: false,
+            spawn: SpawnPolicy::CLASSIC,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Totals {
+    score: i64,
+    steps: u64,
+    completed: u64,
+    elapsed: Duration,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        games: 10,
+        first_seed: 0,
+        width: 16,
+        height: 12,
+        max_steps: 20_000,
+        strategies: Vec::new(),
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--games" => options.games = value()?.parse().map_err(|_| "bad --games")?,
+            "--seed" => options.first_seed = value()?.parse().map_err(|_| "bad --seed")?,
+            "--max-steps" => options.max_steps = value()?.parse().map_err(|_| "bad --max-steps")?,
+            "--strategy" => options.strategies.push(value()?),
+            "--size" => {
+                let size = value()?;
+                let (width, height) = size.split_once('x').ok_or("--size is WIDTHxHEIGHT")?;
+                options.width = width.parse().map_err(|_| "bad --size width")?;
+                options.height = height.parse().map_err(|_| "bad --size height")?;
+            }
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+
+    options
+        .config()
+        .validate()
+        .map_err(|err| format!("bad --size: {}", err))?;
+    Ok(options)
+}
+
+/// Plays one game to the end. Besides dying or filling the board, a game
+/// ends when the step budget runs out or the snake goes so long without
+/// eating that it is clearly circling.
+fn play(controller: &mut dyn Controller, config: GameConfig, seed: u64, max_steps: u32) -> Game {
+    let stall_limit = (config.width * config.height * 4) as u32;
+    let mut game = Game::new(config, seed);
+    let mut last_meal = 0;
+
+    while !game.is_over() && !game.is_complete() && game.ticks < max_steps {
+        if game.ticks - last_meal > stall_limit {
+            break;
+        }
+
+        let direction = controller.next_direction(&game, SnakeId::Player);
+        if let StepEvent::Ate = game.step(Some(direction)) {
+            last_meal = game.ticks;
+        }
+    }
+
+    game
+}
+
+fn main() {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!(
+                "usage: snake_bench [--games N] [--seed N] [--size WxH] [--max-steps N] [--strategy NAME]..."
+            );
+            std::process::exit(2);
+        }
+    };
+
+    let config = options.config();
+
+    println!(
+        "{} games per strategy on a {}x{} board, seeds {}..{}",
+        options.games,
+        options.width,
+        options.height,
+        options.first_seed,
+        options.first_seed + options.games
+    );
+    println!(
+        "{:<12} {:>10} {:>10} {:>11} {:>10} {:>9}",
+        "strategy", "avg score", "completed", "steps/food", "avg steps", "time"
+    );
+
+    for mut controller in ai::controllers() {
+        if !options.strategies.is_empty()
+            && !options
+                .strategies
+                .iter()
+                .any(|name| name == controller.name())
+        {
+            continue;
+        }
+
+        let mut totals = Totals::default();
+        for seed in options.first_seed..options.first_seed + options.games {
+            let started = Instant::now();
+            let game = play(controller.as_mut(), config, seed, options.max_steps);
+            totals.elapsed += started.elapsed();
+            totals.score += game.score as i64;
+            totals.steps += game.ticks as u64;
+            if game.is_complete() {
+                totals.completed += 1;
+            }
+        }
+
+        let games = options.games.max(1) as f64;
+        let steps_per_food = if totals.score > 0 {
+            totals.steps as f64 / totals.score as f64
+        } else {
+            0.0
+        };
+        println!(
+            "{:<12} {:>10.1} {:>9.1}% {:>11.1} {:>10.0} {:>8.2}s",
+            controller.name(),
+            totals.score as f64 / games,
+            totals.completed as f64 * 100.0 / games,
+            steps_per_food,
+            totals.steps as f64 / games,
+            totals.elapsed.as_secs_f64()
+        );
+    }
+}
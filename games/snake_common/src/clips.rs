@@ -0,0 +1,313 @@
+//! Sprite animation driven by named clips loaded from a text file, with
+//! playback state kept per entity so every sprite can be at its own
+//! point in its own clip.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Playback {
+    /// Starts over after the last frame.
+    Loop,
+    /// Runs forwards, then backwards, without repeating the end frames.
+    PingPong,
+    /// Stops on the last frame.
+    Once,
+}
+
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub sprite: usize,
+    pub duration_ms: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Clip {
+    pub name: String,
+    pub playback: Playback,
+    pub frames: Vec<Frame>,
+}
+
+impl Clip {
+    /// The frames in the order they're shown over one full cycle.
+    fn sequence(&self) -> impl Iterator<Item = &Frame> {
+        let back = match self.playback {
+            Playback::PingPong if self.frames.len() > 2 => &self.frames[1..self.frames.len() - 1],
+            _ => &[],
+        };
+        self.frames.iter().chain(back.iter().rev())
+    }
+
+    fn cycle_ms(&self) -> f32 {
+        self.sequence().map(|frame| frame.duration_ms).sum()
+    }
+
+    /// The sprite to show `elapsed_ms` into the clip.
+    pub fn sprite_at(&self, elapsed_ms: f32) -> usize {
+        let cycle = self.cycle_ms();
+        let mut time = match self.playback {
+            Playback::Once => elapsed_ms.min(cycle),
+            _ if cycle > 0.0 => elapsed_ms.rem_euclid(cycle),
+            _ => 0.0,
+        };
+        let mut last = 0;
+        for frame in self.sequence() {
+            if time < frame.duration_ms {
+                return frame.sprite;
+            }
+            time -= frame.duration_ms;
+            last = frame.sprite;
+        }
+        last
+    }
+
+    pub fn is_finished(&self, elapsed_ms: f32) -> bool {
+        self.playback == Playback::Once && elapsed_ms >= self.cycle_ms()
+    }
+}
+
+/// Every clip from a clip file. Lines look like
+///
+/// ```text
+/// walk  loop  66  0 1 2 3:120
+/// ```
+///
+/// giving the name, playback, milliseconds per frame and the sprite
+/// indices, each optionally with its own duration. `#` starts a comment.
+#[derive(Clone, Debug, Default)]
+pub struct Clips {
+    clips: Vec<Clip>,
+}
+
+impl Clips {
+    pub fn new(clips: Vec<Clip>) -> Self {
+        Self { clips }
+    }
+
+    /// Adds the clips of `other` after these ones.
+    pub fn extend(&mut self, other: Clips) {
+        self.clips.extend(other.clips);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut clips = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let clip = parse_clip(line).map_err(|err| format!("line {}: {}", number + 1, err))?;
+            clips.push(clip);
+        }
+        Ok(Self { clips })
+    }
+
+    pub fn len(&self) -> usize {
+        self.clips.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clips.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> &Clip {
+        &self.clips[index]
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.clips.iter().position(|clip| clip.name == name)
+    }
+
+    /// Checks every frame shows one of the `sprites` on the sheet, so a
+    /// typo in a clip is caught at load time instead of when it's drawn.
+    pub fn check_sprites(&self, sprites: usize) -> Result<(), String> {
+        for clip in &self.clips {
+            if let Some(frame) = clip.frames.iter().find(|frame| frame.sprite >= sprites) {
+                return Err(format!(
+                    "clip '{}' shows sprite {}, but the sheet has {}",
+                    clip.name, frame.sprite, sprites
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_clip(line: &str) -> Result<Clip, String> {
+    let mut words = line.split_whitespace();
+    let name = words.next().ok_or("missing clip name")?.to_string();
+    let playback = match words.next() {
+        Some("loop") => Playback::Loop,
+        Some("ping-pong") => Playback::PingPong,
+        Some("once") => Playback::Once,
+        Some(other) => return Err(format!("unknown playback '{}'", other)),
+        None => return Err("missing playback".to_string()),
+    };
+    let frame_ms: f32 = words
+        .next()
+        .and_then(|w| w.parse().ok())
+        .ok_or("missing frame duration")?;
+
+    let frames = words
+        .map(|word| {
+            let (sprite, duration) = match word.split_once(':') {
+                Some((sprite, duration)) => (sprite, duration.parse().ok()),
+                None => (word, Some(frame_ms)),
+            };
+            match (sprite.parse(), duration) {
+                (Ok(sprite), Some(duration_ms)) => Ok(Frame {
+                    sprite,
+                    duration_ms,
+                }),
+                _ => Err(format!("bad frame '{}'", word)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if frames.is_empty() {
+        return Err(format!("clip '{}' has no frames", name));
+    }
+
+    Ok(Clip {
+        name,
+        playback,
+        frames,
+    })
+}
+
+/// Where one entity is in its current clip.
+#[derive(Clone, Copy, Debug)]
+pub struct Animator {
+    pub clip: usize,
+    pub elapsed_ms: f32,
+    /// Playback rate, 1.0 being the clip's own timing.
+    pub speed: f32,
+}
+
+impl Animator {
+    /// Starts `clip` `phase_ms` in, so entities started together don't
+    /// move in lockstep.
+    pub fn new(clip: usize, phase_ms: f32) -> Self {
+        Self {
+            clip,
+            elapsed_ms: phase_ms,
+            speed: 1.0,
+        }
+    }
+
+    pub fn play(&mut self, clip: usize) {
+        if self.clip != clip {
+            self.clip = clip;
+            self.elapsed_ms = 0.0;
+        }
+    }
+
+    pub fn update(&mut self, frame_time_ms: f32) {
+        self.elapsed_ms += frame_time_ms * self.speed;
+    }
+
+    pub fn sprite(&self, clips: &Clips) -> usize {
+        clips.get(self.clip).sprite_at(self.elapsed_ms)
+    }
+
+    pub fn is_finished(&self, clips: &Clips) -> bool {
+        clips.get(self.clip).is_finished(self.elapsed_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIPS: &str = "
+        # name  playback   ms   frames
+        walk    loop       100  0 1 2
+        wave    ping-pong  100  3 4 5 6
+        fall    once       50   7 8:200 9
+    ";
+
+    /// Which sprite shows at each of `times`, playing `name` from the
+    /// start at normal speed.
+    fn sprites_at(clips: &Clips, name: &str, times: &[f32]) -> Vec<usize> {
+        let mut animator = Animator::new(clips.find(name).unwrap(), 0.0);
+        let mut now = 0.0;
+        times
+            .iter()
+            .map(|time| {
+                animator.update(time - now);
+                now = *time;
+                animator.sprite(clips)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_starts_over() {
+        let clips = Clips::parse(CLIPS).unwrap();
+        assert_eq!(
+            sprites_at(&clips, "walk", &[0.0, 99.0, 100.0, 250.0, 300.0, 410.0]),
+            [0, 0, 1, 2, 0, 1]
+        );
+        let walk = Animator::new(clips.find("walk").unwrap(), 10_000.0);
+        assert!(!walk.is_finished(&clips));
+    }
+
+    #[test]
+    fn ping_pong_skips_the_ends_on_the_way_back() {
+        let clips = Clips::parse(CLIPS).unwrap();
+        let times: Vec<f32> = (0..8).map(|i| i as f32 * 100.0 + 50.0).collect();
+        assert_eq!(sprites_at(&clips, "wave", &times), [3, 4, 5, 6, 5, 4, 3, 4]);
+    }
+
+    #[test]
+    fn once_holds_its_last_frame() {
+        let clips = Clips::parse(CLIPS).unwrap();
+        assert_eq!(
+            sprites_at(&clips, "fall", &[0.0, 50.0, 249.0, 250.0, 299.0, 5000.0]),
+            [7, 8, 8, 9, 9, 9]
+        );
+
+        let mut fall = Animator::new(clips.find("fall").unwrap(), 0.0);
+        fall.update(299.0);
+        assert!(!fall.is_finished(&clips));
+        fall.update(1.0);
+        assert!(fall.is_finished(&clips));
+    }
+
+    #[test]
+    fn frames_can_override_the_duration() {
+        let clips = Clips::parse(CLIPS).unwrap();
+        let fall = clips.get(clips.find("fall").unwrap());
+        let durations: Vec<f32> = fall.frames.iter().map(|f| f.duration_ms).collect();
+        assert_eq!(durations, [50.0, 200.0, 50.0]);
+    }
+
+    #[test]
+    fn speed_scales_time() {
+        let clips = Clips::parse(CLIPS).unwrap();
+        let mut walk = Animator::new(clips.find("walk").unwrap(), 0.0);
+        walk.speed = 2.0;
+        walk.update(50.0);
+        assert_eq!(walk.sprite(&clips), 1);
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        let err = Clips::parse("walk loop 100 0 1\nrun bounce 100 0 1").unwrap_err();
+        assert!(err.starts_with("line 2"), "{}", err);
+        assert!(err.contains("unknown playback 'bounce'"), "{}", err);
+
+        let err = Clips::parse("walk loop 100 0 one 2").unwrap_err();
+        assert!(err.contains("bad frame 'one'"), "{}", err);
+        let err = Clips::parse("walk loop 100 0 1:fast").unwrap_err();
+        assert!(err.contains("bad frame '1:fast'"), "{}", err);
+    }
+
+    #[test]
+    fn checks_sprites_are_on_the_sheet() {
+        let clips = Clips::parse("walk loop 100 0 1 2\nfall once 100 3:50").unwrap();
+        assert!(clips.check_sprites(4).is_ok());
+        let err = clips.check_sprites(3).unwrap_err();
+        assert!(
+            err.contains("'fall'") && err.contains("sprite 3"),
+            "{}",
+            err
+        );
+    }
+}
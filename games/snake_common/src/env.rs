@@ -0,0 +1,421 @@
+//! A Gym-style environment over the snake rules, for training agents.
+//!
+//! Call [`SnakeEnv::reset`] with a seed to start an episode, then
+//! [`SnakeEnv::step`] with one action per tick until it reports `done`.
+//! Nothing here touches `BTerm`, so episodes run as fast as the rules do.
+
+use bracket_lib::prelude::{DistanceAlg, Point};
+
+use crate::{Direction, Game, GameConfig, SpawnPolicy, StepEvent};
+
+/// Moves are relative to where the snake is heading, so every action is
+/// always legal.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Straight,
+    TurnLeft,
+    TurnRight,
+}
+
+impl Action {
+    pub const ALL: [Action; 3] = [Action::Straight, Action::TurnLeft, Action::TurnRight];
+
+    pub fn from_index(index: usize) -> Option<Action> {
+        Self::ALL.get(index).copied()
+    }
+
+    fn apply(&self, heading: Direction) -> Direction {
+        match (self, heading) {
+            (Action::Straight, heading) => heading,
+            (Action::TurnLeft, Direction::Up) | (Action::TurnRight, Direction::Down) => {
+                Direction::Left
+            }
+            (Action::TurnLeft, Direction::Down) | (Action::TurnRight, Direction::Up) => {
+                Direction::Right
+            }
+            (Action::TurnLeft, Direction::Left) | (Action::TurnRight, Direction::Right) => {
+                Direction::Down
+            }
+            (Action::TurnLeft, Direction::Right) | (Action::TurnRight, Direction::Left) => {
+                Direction::Up
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ObservationKind {
+    /// Three `height x width` planes, channels first: body, head, food.
+    Grid,
+    /// For each of 8 directions around the head, the inverse distance to
+    /// the wall, the nearest body cell and the food, followed by a one-hot
+    /// of the current heading: 28 values.
+    Rays,
+}
+
+/// A flat tensor of features along with its shape.
+#[derive(Clone, Debug)]
+pub struct Observation {
+    pub shape: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RewardShaping {
+    pub food: f32,
+    pub death: f32,
+    /// Added on every step, usually a small negative number.
+    pub step: f32,
+    /// Paid for each cell the head gets closer to the food, and taken back
+    /// for each cell it moves away.
+    pub approach: f32,
+}
+
+impl Default for RewardShaping {
+    fn default() -> Self {
+        Self {
+            food: 1.0,
+            death: -1.0,
+            step: 0.0,
+            approach: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EnvConfig {
+    pub width: i32,
+    pub height: i32,
+    pub initial_length: i32,
+    pub observation: ObservationKind,
+    pub rewards: RewardShaping,
+    /// Ends an episode that goes this many steps without eating.
+    pub max_steps_without_food: u32,
+}
+
+impl EnvConfig {
+    /// Checks the board has room for the snake, see [`GameConfig::validate`].
+    pub fn validate(&self) -> Result<(), String> {
+        SnakeEnv::game_config(self).validate()
+    }
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            width: 10,
+            height: 10,
+            initial_length: 3,
+            observation: ObservationKind::Grid,
+            rewards: RewardShaping::default(),
+            max_steps_without_food: 100,
+        }
+    }
+}
+
+pub struct SnakeEnv {
+    config: EnvConfig,
+    game: Game,
+    steps_since_food: u32,
+}
+
+impl SnakeEnv {
+    pub fn new(config: EnvConfig) -> Result<Self, String> {
+        config.validate()?;
+        Ok(Self {
+            game: Game::new(Self::game_config(&config), 0),
+            config,
+            steps_since_food: 0,
+        })
+    }
+
+    fn game_config(config: &EnvConfig) -> GameConfig {
+        GameConfig {
+            width: config.width,
+            height: config.height,
+            initial_length: config.initial_length,
+            opponent: false,
+            power_ups: false,
+            spawn: SpawnPolicy::CLASSIC,
+        }
+    }
+
+    /// The settings the environment was built with. They are checked once in
+    /// [`SnakeEnv::new`], so they can't be changed afterwards.
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    /// The game behind the environment, e.g. for rendering an episode.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn action_count(&self) -> usize {
+        Action::ALL.len()
+    }
+
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game = Game::new(Self::game_config(&self.config), seed);
+        self.steps_since_food = 0;
+        self.observe()
+    }
+
+    pub fn step(&mut self, action: Action) -> (Observation, f32, bool) {
+        if self.is_done() {
+            return (self.observe(), 0.0, true);
+        }
+
+        let rewards = self.config.rewards;
+        let before = self.food_distance();
+        let direction = action.apply(self.game.snake.direction);
+
+        let mut reward = rewards.step;
+        match self.game.step(Some(direction)) {
+            StepEvent::Ate => {
+                reward += rewards.food;
+                self.steps_since_food = 0;
+            }
+            StepEvent::Died(_) => reward += rewards.death,
+            StepEvent::Moved => {
+                self.steps_since_food += 1;
+                if let (Some(before), Some(after)) = (before, self.food_distance()) {
+                    reward += rewards.approach * (before - after);
+                }
+            }
+        }
+
+        (self.observe(), reward, self.is_done())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.game.is_over()
+            || self.game.is_complete()
+            || self.steps_since_food >= self.config.max_steps_without_food
+    }
+
+    fn food_distance(&self) -> Option<f32> {
+        self.game
+            .nearest_food(self.game.snake.head())
+            .map(|food| DistanceAlg::Manhattan.distance2d(self.game.snake.head(), food.cell))
+    }
+
+    pub fn observe(&self) -> Observation {
+        match self.config.observation {
+            ObservationKind::Grid => self.grid(),
+            ObservationKind::Rays => self.rays(),
+        }
+    }
+
+    fn grid(&self) -> Observation {
+        let (width, height) = (self.config.width as usize, self.config.height as usize);
+        let plane = width * height;
+        let mut data = vec![0.0; plane * 3];
+        let index = |cell: Point| cell.y as usize * width + cell.x as usize;
+
+        for cell in &self.game.snake.body {
+            if self.game.in_bounds(*cell) {
+                data[index(*cell)] = 1.0;
+            }
+        }
+        data[plane + index(self.game.snake.head())] = 1.0;
+        for food in &self.game.foods {
+            data[plane * 2 + index(food.cell)] = 1.0;
+        }
+
+        Observation {
+            shape: vec![3, height, width],
+            data,
+        }
+    }
+
+    fn rays(&self) -> Observation {
+        const RAYS: [(i32, i32); 8] = [
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ];
+
+        let head = self.game.snake.head();
+        let mut data = Vec::with_capacity(RAYS.len() * 3 + 4);
+
+        for (dx, dy) in RAYS {
+            let mut body = 0.0;
+            let mut food = 0.0;
+            let mut cell = head;
+            let mut distance = 0.0;
+            loop {
+                cell = Point::new(cell.x + dx, cell.y + dy);
+                distance += 1.0;
+                if !self.game.in_bounds(cell) {
+                    break;
+                }
+                if body == 0.0 && self.game.snake.occupies(cell) {
+                    body = 1.0 / distance;
+                }
+                if food == 0.0 && self.game.food_at(cell).is_some() {
+                    food = 1.0 / distance;
+                }
+            }
+            data.extend([1.0 / distance, body, food]);
+        }
+
+        data.extend(Direction::ALL.iter().map(|d| {
+            if *d == self.game.snake.direction {
+                1.0
+            } else {
+                0.0
+            }
+        }));
+
+        Observation {
+            shape: vec![data.len()],
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bracket_lib::prelude::RandomNumberGenerator;
+
+    use super::*;
+    use crate::{Food, FoodKind};
+
+    /// Plays random actions from `seed` until the episode ends, checking
+    /// every observation is `len` values long.
+    fn run_episode(observation: ObservationKind, seed: u64, len: usize) {
+        let mut env = SnakeEnv::new(EnvConfig {
+            observation,
+            ..EnvConfig::default()
+        })
+        .unwrap();
+        let mut rng = RandomNumberGenerator::seeded(seed);
+
+        let first = env.reset(seed);
+        assert_eq!(first.data.len(), len);
+        assert_eq!(first.shape.iter().product::<usize>(), len);
+
+        let mut done = false;
+        let mut steps = 0;
+        while !done {
+            let action = Action::ALL[rng.range(0, Action::ALL.len())];
+            let (next, _, finished) = env.step(action);
+            assert_eq!(next.data.len(), len);
+            done = finished;
+            steps += 1;
+            assert!(steps < 10_000, "episode never ended");
+        }
+        assert!(env.is_done());
+    }
+
+    #[test]
+    fn grid_episode_runs_to_done() {
+        run_episode(ObservationKind::Grid, 3, 3 * 10 * 10);
+    }
+
+    #[test]
+    fn rays_episode_runs_to_done() {
+        run_episode(ObservationKind::Rays, 3, 8 * 3 + 4);
+    }
+
+    #[test]
+    fn rejects_boards_the_snake_doesnt_fit() {
+        let config = |width, height, initial_length| EnvConfig {
+            width,
+            height,
+            initial_length,
+            ..EnvConfig::default()
+        };
+        assert!(SnakeEnv::new(config(0, 10, 1)).is_err());
+        assert!(SnakeEnv::new(config(10, -1, 1)).is_err());
+        assert!(SnakeEnv::new(config(10, 10, 0)).is_err());
+        assert!(SnakeEnv::new(config(10, 10, 4)).is_err());
+        assert!(SnakeEnv::new(config(10, 10, 3)).is_ok());
+        assert!(SnakeEnv::new(config(1, 1, 1)).is_ok());
+    }
+
+    #[test]
+    fn turns_are_relative_to_the_heading() {
+        use Direction::*;
+        let table = [
+            (Up, Left, Right),
+            (Right, Up, Down),
+            (Down, Right, Left),
+            (Left, Down, Up),
+        ];
+        for (heading, left, right) in table {
+            assert_eq!(Action::Straight.apply(heading), heading);
+            assert_eq!(Action::TurnLeft.apply(heading), left, "{:?}", heading);
+            assert_eq!(Action::TurnRight.apply(heading), right, "{:?}", heading);
+        }
+    }
+
+    /// A 10x10 board with the snake's head at (2, 5), heading right, and
+    /// a single normal food at `food`.
+    fn shaped_env(food: Point) -> SnakeEnv {
+        let mut env = SnakeEnv::new(EnvConfig {
+            rewards: RewardShaping {
+                food: 10.0,
+                death: -5.0,
+                step: -0.25,
+                approach: 0.5,
+            },
+            ..EnvConfig::default()
+        })
+        .unwrap();
+        env.reset(1);
+        env.game.foods = vec![Food {
+            cell: food,
+            kind: FoodKind::Normal,
+            expires_at: None,
+        }];
+        env
+    }
+
+    #[test]
+    fn pays_the_shaped_rewards() {
+        let mut env = shaped_env(Point::new(3, 5));
+        assert_eq!(env.step(Action::Straight).1, 10.0 - 0.25);
+
+        let mut env = shaped_env(Point::new(6, 5));
+        assert_eq!(env.step(Action::Straight).1, 0.5 - 0.25);
+
+        let mut env = shaped_env(Point::new(0, 0));
+        assert_eq!(env.step(Action::TurnRight).1, -0.5 - 0.25);
+
+        let mut env = shaped_env(Point::new(0, 0));
+        env.game.snake.body = vec![Point::new(9, 5), Point::new(8, 5), Point::new(7, 5)];
+        let (_, reward, done) = env.step(Action::Straight);
+        assert_eq!(reward, -5.0 - 0.25);
+        assert!(done);
+    }
+
+    #[test]
+    fn grid_marks_body_head_and_food_planes() {
+        let env = shaped_env(Point::new(7, 1));
+        let observation = env.observe();
+        assert_eq!(observation.shape, vec![3, 10, 10]);
+
+        let plane = |channel: usize| {
+            let cells = &observation.data[channel * 100..(channel + 1) * 100];
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| **value == 1.0)
+                .map(|(i, _)| Point::new(i as i32 % 10, i as i32 / 10))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            plane(0),
+            vec![Point::new(0, 5), Point::new(1, 5), Point::new(2, 5)]
+        );
+        assert_eq!(plane(1), vec![Point::new(2, 5)]);
+        assert_eq!(plane(2), vec![Point::new(7, 1)]);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::storage;
+
+const MAX_ENTRIES: usize = 10;
+
+/// Best scores of a game, kept sorted from best to worst and stored as
+/// one score per line in a small text file, or in local storage when
+/// running in a browser.
+pub struct HighScores {
+    path: String,
+    scores: Vec<i32>,
+}
+
+impl HighScores {
+    pub fn load<S: ToString>(path: S) -> Self {
+        let path = path.to_string();
+        let mut scores: Vec<i32> = storage::read(&path)
+            .map(|contents| {
+                contents
+                    .lines()
+                    .filter_map(|line| line.trim().parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        scores.sort_unstable_by(|a, b| b.cmp(a));
+        scores.truncate(MAX_ENTRIES);
+
+        Self { path, scores }
+    }
+
+    pub fn scores(&self) -> &[i32] {
+        &self.scores
+    }
+
+    pub fn best(&self) -> i32 {
+        self.scores.first().copied().unwrap_or(0)
+    }
+
+    /// Records a finished run, returning whether it made it into the table.
+    pub fn submit(&mut self, score: i32) -> bool {
+        if score <= 0 {
+            return false;
+        }
+
+        let position = self
+            .scores
+            .iter()
+            .position(|s| score > *s)
+            .unwrap_or(self.scores.len());
+        if position >= MAX_ENTRIES {
+            return false;
+        }
+
+        self.scores.insert(position, score);
+        self.scores.truncate(MAX_ENTRIES);
+
+        let contents: String = self.scores.iter().map(|s| format!("{}\n", s)).collect();
+        if let Err(err) = storage::write(&self.path, &contents) {
+            eprintln!("could not save high scores to {}: {}", self.path, err);
+        }
+
+        true
+    }
+}
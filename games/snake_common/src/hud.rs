@@ -0,0 +1,97 @@
+use bracket_lib::prelude::*;
+
+use crate::Game;
+
+/// The heads-up display shown while playing: score, best score, length,
+/// speed level, time alive and running power-ups, plus FPS and engine
+/// details when debug info is on.
+///
+/// Games draw it on a console of its own, layered above the playfield,
+/// so it can be cleared and toggled without touching the board.
+pub struct Hud {
+    pub visible: bool,
+    pub debug: bool,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        // colour names used in the `printer` markup below
+        register_palette_color("pink", MAGENTA);
+        register_palette_color("yellow", YELLOW);
+        register_palette_color("cyan", CYAN);
+        register_palette_color("grey", GREY);
+
+        Self {
+            visible: true,
+            debug: false,
+        }
+    }
+
+    /// H shows or hides the HUD and F3 the debug info. Returns whether
+    /// the key was one of those.
+    pub fn handle_key(&mut self, key: Option<VirtualKeyCode>) -> bool {
+        match key {
+            Some(VirtualKeyCode::H) => self.visible = !self.visible,
+            Some(VirtualKeyCode::F3) => self.debug = !self.debug,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draws onto the active console, starting at row `top`.
+    pub fn render(&self, ctx: &mut BTerm, top: i32, game: &Game, high_score: i32, time_ms: f32) {
+        if !self.visible {
+            return;
+        }
+
+        let seconds = (time_ms / 1000.0) as i32;
+        let mut line = format!(
+            "#[yellow]Score #[]{}  #[yellow]Best #[]{}  #[yellow]Length #[]{}  #[yellow]Level #[]{}  #[yellow]Time #[]{}:{:02}",
+            game.score,
+            high_score.max(game.score),
+            game.snake.length(),
+            game.level(),
+            seconds / 60,
+            seconds % 60
+        );
+        if game.config.opponent {
+            line.push_str(&format!("  #[cyan]CPU #[]{}", game.opponent_score));
+        }
+        // power-ups count down in moves, not seconds
+        for active in &game.effects {
+            line.push_str(&format!(
+                "  #[pink]{} #[]{}",
+                active.effect.label(),
+                game.effect_remaining(active.effect)
+            ));
+        }
+        ctx.printer(1, top, line, TextAlign::Left, None);
+
+        if self.debug {
+            let (columns, _) = ctx.get_char_size();
+            ctx.printer(
+                columns as i32 - 2,
+                top,
+                format!("#[pink]FPS: #[]{}", ctx.fps as i32),
+                TextAlign::Right,
+                None,
+            );
+            ctx.printer(
+                1,
+                top + 1,
+                format!(
+                    "#[grey]tick {}  seed {}  board {}x{}  frame {:.1}ms",
+                    game.ticks, game.seed, game.config.width, game.config.height, ctx.frame_time_ms
+                ),
+                TextAlign::Left,
+                None,
+            );
+        }
+    }
+}
+
+impl Default for Hud {
+    fn default() -> Self {
+        Self::new()
+    }
+}
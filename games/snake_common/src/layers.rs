@@ -0,0 +1,63 @@
+//! Z-orders for the sprite console, worked out the same way everywhere.
+//!
+//! Bracket sorts sprites by the z-order given to `add_sprite` before
+//! drawing them, lowest first, and keeps sprites with the same z-order in
+//! the order they were added. Every layer gets its own band of z-orders,
+//! so a whole layer draws over the ones before it. Within
+//! [`Layer::Entities`] sprites are usually y-sorted: one standing lower
+//! on the screen is nearer the viewer and draws in front.
+//!
+//! ```ignore
+//! ctx.add_sprite(rect, Layer::Entities.y_sorted(rect, 0), tint, dood);
+//! ctx.add_sprite(spark, Layer::Effects.z(), tint, particle);
+//! ```
+
+use bracket_lib::prelude::Rect;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Layer {
+    /// Floors and backdrops, behind everything else.
+    Background,
+    /// Things that stand on the ground and overlap each other.
+    Entities,
+    /// Particles and flashes over the entities.
+    Effects,
+    /// Anything that must stay on top, such as a cursor.
+    Foreground,
+}
+
+/// How many z-orders each layer has to itself.
+const LAYER_SPAN: i32 = 1 << 24;
+/// Tie-breaking ranks per row of pixels in a y-sorted layer.
+const RANKS: i32 = 16;
+/// Rows above the top of the screen that still sort correctly, for
+/// sprites partly off it or shaken about.
+const ROWS_ABOVE: i32 = 1 << 16;
+
+impl Layer {
+    pub const ALL: [Layer; 4] = [
+        Layer::Background,
+        Layer::Entities,
+        Layer::Effects,
+        Layer::Foreground,
+    ];
+
+    fn base(self) -> i32 {
+        self as i32 * LAYER_SPAN
+    }
+
+    /// Z-order for a sprite that draws in the order it is added, over
+    /// everything in the layers before this one.
+    pub fn z(self) -> i32 {
+        self.base()
+    }
+
+    /// Z-order for a sprite sorted by where its bottom edge is, so the
+    /// lower of two overlapping sprites draws in front. `rank` breaks
+    /// ties between sprites on the same row, highest in front, and goes
+    /// up to 15.
+    pub fn y_sorted(self, rect: Rect, rank: u8) -> i32 {
+        let row = (rect.y2 + ROWS_ABOVE).clamp(0, LAYER_SPAN / RANKS - 1);
+        self.base() + row * RANKS + (rank as i32).min(RANKS - 1)
+    }
+}
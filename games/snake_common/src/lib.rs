@@ -0,0 +1,64 @@
+use bracket_lib::prelude::Point;
+
+pub mod ai;
+pub mod aseprite;
+pub mod assets;
+pub mod clips;
+pub mod env;
+pub mod highscores;
+pub mod hud;
+pub mod layers;
+pub mod main_menu;
+pub mod menu;
+pub mod replay;
+pub mod settings;
+pub mod sheet;
+pub mod sim;
+pub mod storage;
+
+pub use ai::{AStarController, Controller};
+pub use highscores::HighScores;
+pub use hud::Hud;
+pub use main_menu::{MainMenu, MenuAction};
+pub use menu::{Menu, MenuEvent};
+pub use replay::{Replay, ReplayPlayer};
+pub use settings::{ControlScheme, Difficulty, FoodMode, Settings, Theme};
+pub use sim::{
+    Death, DeathCause, Effect, Food, FoodKind, Game, GameConfig, Snake, SnakeId, SpawnPolicy,
+    StepEvent,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Up,
+    Down,
+    Right,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Right,
+        Direction::Left,
+    ];
+
+    pub fn delta(&self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Down => Point::new(0, 1),
+            Direction::Right => Point::new(1, 0),
+            Direction::Left => Point::new(-1, 0),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Right => Direction::Left,
+            Direction::Left => Direction::Right,
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use bracket_lib::prelude::*;
+
+use crate::{ControlScheme, Difficulty, FoodMode, HighScores, Menu, MenuEvent, Settings, Theme};
+
+const ATTRACT_MODE_DELAY_MS: f32 = 20_000.0;
+
+pub enum MenuAction {
+    Play,
+    PlayVersusCpu,
+    Demo,
+    WatchReplay,
+    Quit,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Screen {
+    Main,
+    Options,
+    Difficulty,
+    Theme,
+    Controls,
+    FoodMode,
+    HighScores,
+}
+
+#[derive(Clone, Copy)]
+enum Item {
+    Play,
+    PlayVersusCpu,
+    Demo,
+    WatchReplay,
+    Quit,
+    Open(Screen),
+    SetDifficulty(Difficulty),
+    SetTheme(Theme),
+    SetControls(ControlScheme),
+    SetFoodMode(FoodMode),
+    TogglePowerUps,
+    ToggleReducedMotion,
+    Back,
+}
+
+/// The title screen shared by the snake games: play, quit, and the
+/// options / high score submenus. Owns the player's settings.
+pub struct MainMenu {
+    title: String,
+    screen: Screen,
+    menu: Menu<Item>,
+    idle_ms: f32,
+    /// Shown under the menu until the next key press, e.g. why the last
+    /// run can't be watched.
+    message: Option<String>,
+    pub settings: Settings,
+    pub high_scores: HighScores,
+}
+
+impl MainMenu {
+    pub fn new<S: ToString>(title: S, high_scores: HighScores) -> Self {
+        let settings = Settings::default();
+        Self {
+            title: title.to_string(),
+            screen: Screen::Main,
+            menu: build_menu(Screen::Main, &settings),
+            idle_ms: 0.0,
+            message: None,
+            settings,
+            high_scores,
+        }
+    }
+
+    /// Shows `message` under the menu until a key is pressed.
+    pub fn report<S: ToString>(&mut self, message: S) {
+        self.message = Some(message.to_string());
+    }
+
+    /// Handles this frame's input and draws the current screen on the
+    /// active console. Returns what the game should do, if anything.
+    /// Left alone on the title screen long enough, it starts the demo.
+    pub fn tick(&mut self, ctx: &mut BTerm) -> Option<MenuAction> {
+        let mut action = None;
+
+        self.idle_ms = if ctx.key.is_some() {
+            self.message = None;
+            0.0
+        } else {
+            self.idle_ms + ctx.frame_time_ms
+        };
+        if self.screen == Screen::Main && self.idle_ms > ATTRACT_MODE_DELAY_MS {
+            self.idle_ms = 0.0;
+            action = Some(MenuAction::Demo);
+        }
+
+        match self.menu.handle_key(ctx.key) {
+            MenuEvent::Selected(Item::Play) => action = Some(MenuAction::Play),
+            MenuEvent::Selected(Item::PlayVersusCpu) => action = Some(MenuAction::PlayVersusCpu),
+            MenuEvent::Selected(Item::Demo) => action = Some(MenuAction::Demo),
+            MenuEvent::Selected(Item::WatchReplay) => action = Some(MenuAction::WatchReplay),
+            MenuEvent::Selected(Item::Quit) => action = Some(MenuAction::Quit),
+            MenuEvent::Selected(Item::Open(screen)) => self.open(screen),
+            MenuEvent::Selected(Item::SetDifficulty(difficulty)) => {
+                self.settings.difficulty = difficulty;
+                self.open(Screen::Options);
+            }
+            MenuEvent::Selected(Item::SetTheme(theme)) => {
+                self.settings.theme = theme;
+                self.open(Screen::Options);
+            }
+            MenuEvent::Selected(Item::SetControls(controls)) => {
+                self.settings.controls = controls;
+                self.open(Screen::Options);
+            }
+            MenuEvent::Selected(Item::SetFoodMode(food_mode)) => {
+                self.settings.food_mode = food_mode;
+                self.open(Screen::Options);
+            }
+            MenuEvent::Selected(Item::TogglePowerUps) => {
+                self.settings.power_ups = !self.settings.power_ups;
+                self.refresh();
+            }
+            MenuEvent::Selected(Item::ToggleReducedMotion) => {
+                self.settings.reduced_motion = !self.settings.reduced_motion;
+                self.refresh();
+            }
+            MenuEvent::Selected(Item::Back) | MenuEvent::Back => self.back(),
+            MenuEvent::None => {}
+        }
+
+        self.render(ctx);
+        action
+    }
+
+    fn open(&mut self, screen: Screen) {
+        self.screen = screen;
+        self.menu = build_menu(screen, &self.settings);
+    }
+
+    /// Rebuilds the current screen after a setting changed in place,
+    /// keeping the cursor where it was.
+    fn refresh(&mut self) {
+        let cursor = self.menu.cursor();
+        self.menu = build_menu(self.screen, &self.settings).with_cursor(cursor);
+    }
+
+    fn back(&mut self) {
+        match self.screen {
+            Screen::Main => {}
+            Screen::Options | Screen::HighScores => self.open(Screen::Main),
+            Screen::Difficulty | Screen::Theme | Screen::Controls | Screen::FoodMode => {
+                self.open(Screen::Options)
+            }
+        }
+    }
+
+    fn render(&self, ctx: &mut BTerm) {
+        ctx.cls();
+        ctx.print_centered(5, &self.title);
+        self.menu.render(ctx, 8);
+
+        if self.screen == Screen::HighScores {
+            if self.high_scores.scores().is_empty() {
+                ctx.print_centered(14, "No scores yet");
+            }
+            for (rank, score) in self.high_scores.scores().iter().enumerate() {
+                ctx.print_centered(14 + rank as i32, format!("{:>2}. {:>6}", rank + 1, score));
+            }
+        }
+
+        // keep the hint at the bottom, whatever size the console is
+        let (columns, rows) = ctx.get_char_size();
+        if let Some(message) = &self.message {
+            let message: String = message.chars().take(columns as usize).collect();
+            ctx.print_color_centered(rows as i32 - 5, RED, BLACK, message);
+        }
+        ctx.print_color_centered(
+            rows as i32 - 3,
+            GREY,
+            BLACK,
+            "Up/Down to move, Enter to select, Esc to go back",
+        );
+    }
+}
+
+fn build_menu(screen: Screen, settings: &Settings) -> Menu<Item> {
+    match screen {
+        Screen::Main => Menu::new("Main Menu")
+            .with_item("Play Game", Item::Play)
+            .with_item("Play vs CPU", Item::PlayVersusCpu)
+            .with_item("Watch Demo", Item::Demo)
+            .with_item("Watch Last Run", Item::WatchReplay)
+            .with_item("Options", Item::Open(Screen::Options))
+            .with_item("High Scores", Item::Open(Screen::HighScores))
+            .with_item("Quit Game", Item::Quit),
+        Screen::Options => Menu::new("Options")
+            .with_item(
+                format!("Difficulty: {}", settings.difficulty.label()),
+                Item::Open(Screen::Difficulty),
+            )
+            .with_item(
+                format!("Theme: {}", settings.theme.label()),
+                Item::Open(Screen::Theme),
+            )
+            .with_item(
+                format!("Controls: {}", settings.controls.label()),
+                Item::Open(Screen::Controls),
+            )
+            .with_item(
+                format!(
+                    "Power-ups: {}",
+                    if settings.power_ups { "On" } else { "Off" }
+                ),
+                Item::TogglePowerUps,
+            )
+            .with_item(
+                format!("Food: {}", settings.food_mode.label()),
+                Item::Open(Screen::FoodMode),
+            )
+            .with_item(
+                format!(
+                    "Reduced motion: {}",
+                    if settings.reduced_motion { "On" } else { "Off" }
+                ),
+                Item::ToggleReducedMotion,
+            )
+            .with_item("Back", Item::Back),
+        Screen::Difficulty => Difficulty::ALL
+            .iter()
+            .fold(Menu::new("Difficulty"), |menu, d| {
+                menu.with_item(d.label(), Item::SetDifficulty(*d))
+            })
+            .with_cursor(
+                Difficulty::ALL
+                    .iter()
+                    .position(|d| *d == settings.difficulty)
+                    .unwrap_or(0),
+            ),
+        Screen::Theme => Theme::ALL
+            .iter()
+            .fold(Menu::new("Theme"), |menu, t| {
+                menu.with_item(t.label(), Item::SetTheme(*t))
+            })
+            .with_cursor(
+                Theme::ALL
+                    .iter()
+                    .position(|t| *t == settings.theme)
+                    .unwrap_or(0),
+            ),
+        Screen::Controls => ControlScheme::ALL
+            .iter()
+            .fold(Menu::new("Controls"), |menu, c| {
+                menu.with_item(c.label(), Item::SetControls(*c))
+            })
+            .with_cursor(
+                ControlScheme::ALL
+                    .iter()
+                    .position(|c| *c == settings.controls)
+                    .unwrap_or(0),
+            ),
+        Screen::FoodMode => FoodMode::ALL
+            .iter()
+            .fold(Menu::new("Food"), |menu, f| {
+                menu.with_item(f.label(), Item::SetFoodMode(*f))
+            })
+            .with_cursor(
+                FoodMode::ALL
+                    .iter()
+                    .position(|f| *f == settings.food_mode)
+                    .unwrap_or(0),
+            ),
+        Screen::HighScores => Menu::new("High Scores").with_item("Back", Item::Back),
+    }
+}
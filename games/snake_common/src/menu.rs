@@ -0,0 +1,82 @@
+use bracket_lib::prelude::*;
+
+pub enum MenuEvent<T> {
+    None,
+    Selected(T),
+    Back,
+}
+
+struct MenuItem<T> {
+    label: String,
+    action: T,
+}
+
+/// A vertical list of selectable items with a cursor, driven by the
+/// arrow keys (or W/S) and confirmed with Enter.
+pub struct Menu<T> {
+    title: String,
+    items: Vec<MenuItem<T>>,
+    cursor: usize,
+}
+
+impl<T: Copy> Menu<T> {
+    pub fn new<S: ToString>(title: S) -> Self {
+        Self {
+            title: title.to_string(),
+            items: Vec::new(),
+            cursor: 0,
+        }
+    }
+
+    pub fn with_item<S: ToString>(mut self, label: S, action: T) -> Self {
+        self.items.push(MenuItem {
+            label: label.to_string(),
+            action,
+        });
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: usize) -> Self {
+        self.cursor = cursor.min(self.items.len().saturating_sub(1));
+        self
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn handle_key(&mut self, key: Option<VirtualKeyCode>) -> MenuEvent<T> {
+        if self.items.is_empty() {
+            return MenuEvent::None;
+        }
+
+        match key {
+            Some(VirtualKeyCode::Up) | Some(VirtualKeyCode::W) => {
+                self.cursor = (self.cursor + self.items.len() - 1) % self.items.len();
+                MenuEvent::None
+            }
+            Some(VirtualKeyCode::Down) | Some(VirtualKeyCode::S) => {
+                self.cursor = (self.cursor + 1) % self.items.len();
+                MenuEvent::None
+            }
+            Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::Space) => {
+                MenuEvent::Selected(self.items[self.cursor].action)
+            }
+            Some(VirtualKeyCode::Escape) | Some(VirtualKeyCode::Back) => MenuEvent::Back,
+            _ => MenuEvent::None,
+        }
+    }
+
+    pub fn render(&self, ctx: &mut BTerm, top: i32) {
+        ctx.print_color_centered(top, YELLOW, BLACK, &self.title);
+
+        for (index, item) in self.items.iter().enumerate() {
+            let y = top + 3 + index as i32;
+            if index == self.cursor {
+                ctx.print_color_centered(y, BLACK, WHITE, format!("> {} <", item.label));
+            } else {
+                ctx.print_color_centered(y, GREY, BLACK, &item.label);
+            }
+        }
+    }
+}
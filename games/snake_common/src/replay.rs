@@ -0,0 +1,412 @@
+use bracket_lib::prelude::VirtualKeyCode;
+
+use crate::settings::frame_duration_for;
+use crate::{ai, storage, Direction, Game, GameConfig, SpawnPolicy, StepEvent};
+
+const HEADER: &str = "snake-replay";
+/// Version written in the header. 2 added the `powerups` and `spawn`
+/// lines; version 1 files read the same with both left at their defaults.
+const VERSION: u32 = 2;
+const MAX_SPEED: u32 = 16;
+
+/// A recorded run: the seed and board the game started from, plus every
+/// tick on which the player pressed a direction. Replaying those inputs
+/// through [`Game`] reproduces the run exactly.
+///
+/// The CPU opponent is deterministic, so its moves are not stored: only
+/// whether there was one. The `spawn` line holds the food spawn policy
+/// (max foods, interval, distance, lifetime or 0 for none) and is left
+/// out for the classic one. Saved as a short text file:
+///
+/// ```text
+/// snake-replay 2
+/// board 80 50 6
+/// seed 1234567
+/// frame 75
+/// opponent 1
+/// powerups 1
+/// spawn 8 6 4 120
+/// end 412
+/// inputs 12U 40L 41D
+/// ```
+///
+/// Files from a newer version, or with lines this version doesn't know,
+/// are refused rather than played back under the wrong rules.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub config: GameConfig,
+    pub seed: u64,
+    /// Milliseconds per tick at level 1 the run was played at, used for
+    /// playback speed.
+    pub frame_duration: f32,
+    pub inputs: Vec<(u32, Direction)>,
+    /// Tick on which the run ended, if it has.
+    pub end_tick: Option<u32>,
+}
+
+impl Replay {
+    pub fn new(config: GameConfig, seed: u64, frame_duration: f32) -> Self {
+        Self {
+            config,
+            seed,
+            frame_duration,
+            inputs: Vec::new(),
+            end_tick: None,
+        }
+    }
+
+    /// Records the input given to the step that starts at `tick`.
+    pub fn record(&mut self, tick: u32, input: Option<Direction>) {
+        if let Some(direction) = input {
+            self.inputs.push((tick, direction));
+        }
+    }
+
+    pub fn finish(&mut self, tick: u32) {
+        self.end_tick = Some(tick);
+    }
+
+    pub fn save(&self, path: &str) {
+        if let Err(err) = storage::write(path, &self.to_text()) {
+            eprintln!("could not save replay to {}: {}", path, err);
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = storage::read(path).map_err(|err| format!("{}: {}", path, err))?;
+        Self::from_text(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn to_text(&self) -> String {
+        let inputs: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|(tick, direction)| format!("{}{}", tick, direction_code(*direction)))
+            .collect();
+
+        let mut text = format!(
+            "{} {}\nboard {} {} {}\nseed {}\nframe {}\n",
+            HEADER,
+            VERSION,
+            self.config.width,
+            self.config.height,
+            self.config.initial_length,
+            self.seed,
+            self.frame_duration
+        );
+        if self.config.opponent {
+            text.push_str("opponent 1\n");
+        }
+        if self.config.power_ups {
+            text.push_str("powerups 1\n");
+        }
+        let spawn = self.config.spawn;
+        if spawn != SpawnPolicy::CLASSIC {
+            text.push_str(&format!(
+                "spawn {} {} {} {}\n",
+                spawn.max_foods,
+                spawn.interval,
+                spawn.min_distance,
+                spawn.lifetime.unwrap_or(0)
+            ));
+        }
+        if let Some(end_tick) = self.end_tick {
+            text.push_str(&format!("end {}\n", end_tick));
+        }
+        text.push_str(&format!("inputs {}\n", inputs.join(" ")));
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        let version = lines
+            .next()
+            .and_then(|line| line.strip_prefix(HEADER))
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or("not a snake replay")?;
+        if version > VERSION {
+            return Err(format!(
+                "replay format {} is newer than this game reads ({})",
+                version, VERSION
+            ));
+        }
+
+        let mut config = None;
+        let mut opponent = false;
+        let mut power_ups = false;
+        let mut spawn = SpawnPolicy::CLASSIC;
+        let mut seed = None;
+        let mut frame_duration = 75.0;
+        let mut end_tick = None;
+        let mut inputs = Vec::new();
+
+        for line in lines {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("board") => {
+                    let numbers: Vec<i32> = words.filter_map(|w| w.parse().ok()).collect();
+                    if let [width, height, initial_length] = numbers[..] {
+                        config = Some(GameConfig {
+                            width,
+                            height,
+                            initial_length,
+                            opponent: false,
+                            power_ups: false,
+                            spawn: SpawnPolicy::CLASSIC,
+                        });
+                    }
+                }
+                Some("opponent") => opponent = words.next() == Some("1"),
+                Some("powerups") => power_ups = words.next() == Some("1"),
+                Some("spawn") => {
+                    let numbers: Vec<u32> = words.filter_map(|w| w.parse().ok()).collect();
+                    match numbers[..] {
+                        [max_foods, interval, min_distance, lifetime] => {
+                            spawn = SpawnPolicy {
+                                max_foods: max_foods as usize,
+                                interval,
+                                min_distance: min_distance as i32,
+                                lifetime: (lifetime > 0).then_some(lifetime),
+                            }
+                        }
+                        _ => return Err(format!("bad spawn line '{}'", line)),
+                    }
+                }
+                Some("seed") => seed = words.next().and_then(|w| w.parse().ok()),
+                Some("frame") => {
+                    frame_duration = words.next().and_then(|w| w.parse().ok()).unwrap_or(75.0)
+                }
+                Some("end") => end_tick = words.next().and_then(|w| w.parse().ok()),
+                Some("inputs") => {
+                    for word in words {
+                        if !word.is_ascii() {
+                            return Err(format!("bad input '{}'", word));
+                        }
+                        let (tick, code) = word.split_at(word.len() - 1);
+                        match (tick.parse(), parse_direction_code(code)) {
+                            (Ok(tick), Some(direction)) => inputs.push((tick, direction)),
+                            _ => return Err(format!("bad input '{}'", word)),
+                        }
+                    }
+                }
+                Some(key) => return Err(format!("unknown line '{}'", key)),
+                None => {}
+            }
+        }
+
+        let config = config.ok_or("missing board line")?;
+        config.validate()?;
+        Ok(Self {
+            config: GameConfig {
+                opponent,
+                power_ups,
+                spawn,
+                ..config
+            },
+            seed: seed.ok_or("missing seed line")?,
+            frame_duration,
+            inputs,
+            end_tick,
+        })
+    }
+}
+
+fn direction_code(direction: Direction) -> char {
+    match direction {
+        Direction::Up => 'U',
+        Direction::Down => 'D',
+        Direction::Right => 'R',
+        Direction::Left => 'L',
+    }
+}
+
+fn parse_direction_code(code: &str) -> Option<Direction> {
+    match code {
+        "U" => Some(Direction::Up),
+        "D" => Some(Direction::Down),
+        "R" => Some(Direction::Right),
+        "L" => Some(Direction::Left),
+        _ => None,
+    }
+}
+
+/// Plays a [`Replay`] back through a fresh [`Game`], with pause, single
+/// stepping and fast-forward.
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub game: Game,
+    pub paused: bool,
+    pub speed: u32,
+    cursor: usize,
+    timer: f32,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            game: Game::new(replay.config, replay.seed),
+            replay,
+            paused: false,
+            speed: 1,
+            cursor: 0,
+            timer: 0.0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.game.is_over()
+            || self
+                .replay
+                .end_tick
+                .is_some_and(|end| self.game.ticks >= end)
+    }
+
+    /// Plays a single tick of the recording.
+    pub fn step(&mut self) -> Option<StepEvent> {
+        if self.is_finished() {
+            return None;
+        }
+
+        let mut input = None;
+        while let Some((tick, direction)) = self.replay.inputs.get(self.cursor) {
+            if *tick > self.game.ticks {
+                break;
+            }
+            if *tick == self.game.ticks {
+                input = Some(*direction);
+            }
+            self.cursor += 1;
+        }
+
+        let opponent_input = ai::opponent_move(&self.game);
+        Some(self.game.step_versus(input, opponent_input))
+    }
+
+    /// Advances playback by real time, honouring pause and speed.
+    pub fn update(&mut self, frame_time_ms: f32) {
+        if self.paused {
+            return;
+        }
+
+        self.timer += frame_time_ms * self.speed as f32;
+        loop {
+            let frame_duration = frame_duration_for(self.replay.frame_duration, &self.game);
+            if self.timer <= frame_duration || self.is_finished() {
+                break;
+            }
+            self.timer -= frame_duration;
+            self.step();
+        }
+    }
+
+    /// Space pauses, N or Right steps while paused, F cycles the speed
+    /// and R restarts. Returns false once the viewer asks to leave.
+    pub fn handle_key(&mut self, key: Option<VirtualKeyCode>) -> bool {
+        match key {
+            Some(VirtualKeyCode::Space) | Some(VirtualKeyCode::P) => self.paused = !self.paused,
+            Some(VirtualKeyCode::N) | Some(VirtualKeyCode::Right) if self.paused => {
+                self.step();
+            }
+            Some(VirtualKeyCode::F) => {
+                self.speed = if self.speed >= MAX_SPEED {
+                    1
+                } else {
+                    self.speed * 2
+                }
+            }
+            Some(VirtualKeyCode::R) => *self = ReplayPlayer::new(self.replay.clone()),
+            Some(VirtualKeyCode::Escape) | Some(VirtualKeyCode::Q) => return false,
+            _ => {}
+        }
+        true
+    }
+
+    pub fn status_line(&self) -> String {
+        let state = if self.is_finished() {
+            "END"
+        } else if self.paused {
+            "PAUSED"
+        } else {
+            "PLAYING"
+        };
+        format!(
+            "REPLAY {} tick:{} x{}  Space:pause N:step F:speed R:restart Esc:exit",
+            state, self.game.ticks, self.speed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut replay = Replay::new(
+            GameConfig {
+                width: 30,
+                height: 20,
+                initial_length: 4,
+                opponent: true,
+                power_ups: true,
+                spawn: SpawnPolicy::FEEDING_FRENZY,
+            },
+            1234567,
+            62.5,
+        );
+        replay.record(12, Some(Direction::Up));
+        replay.record(13, None);
+        replay.record(40, Some(Direction::Left));
+        replay.finish(412);
+
+        let text = replay.to_text();
+        let read = Replay::from_text(&text).unwrap();
+        assert_eq!(read.config, replay.config);
+        assert_eq!(read.seed, replay.seed);
+        assert_eq!(read.frame_duration, replay.frame_duration);
+        assert_eq!(read.inputs, replay.inputs);
+        assert_eq!(read.end_tick, Some(412));
+        assert_eq!(read.to_text(), text);
+    }
+
+    #[test]
+    fn reads_version_1() {
+        let text = "snake-replay 1\nboard 80 50 6\nseed 7\nframe 75\ninputs 3U\n";
+        let replay = Replay::from_text(text).unwrap();
+        assert!(!replay.config.power_ups);
+        assert_eq!(replay.config.spawn, SpawnPolicy::CLASSIC);
+        assert_eq!(replay.inputs, vec![(3, Direction::Up)]);
+    }
+
+    #[test]
+    fn rejects_bad_header() {
+        for header in ["", "snake-replay", "snake-replay x", "chess-replay 1"] {
+            let text = format!("{}\nboard 80 50 6\nseed 7\ninputs\n", header);
+            assert!(Replay::from_text(&text).is_err(), "{:?}", header);
+        }
+        let newer = format!("snake-replay {}\nboard 80 50 6\nseed 7\n", VERSION + 1);
+        assert!(Replay::from_text(&newer).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_lines() {
+        let text = "snake-replay 2\nboard 80 50 6\nseed 7\nwalls 1\ninputs\n";
+        let err = Replay::from_text(text).unwrap_err();
+        assert!(err.contains("walls"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_bad_board() {
+        for board in [
+            "0 0 6",
+            "80 0 6",
+            "-3 50 6",
+            "80 50 0",
+            "10 10 4",
+            "5000 50 6",
+        ] {
+            let text = format!("snake-replay 2\nboard {}\nseed 7\ninputs\n", board);
+            assert!(Replay::from_text(&text).is_err(), "{}", board);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use bracket_lib::prelude::*;
+
+use crate::{Direction, Game, SpawnPolicy};
+
+/// The fastest levelling up makes the snake move, in milliseconds per
+/// move. Power-ups can still go past it.
+const MIN_FRAME_DURATION: f32 = 30.0;
+
+/// Milliseconds until `game` moves next, given `base`, the time between
+/// two moves at level 1: a tenth less for every level above it, then
+/// scaled by any speed power-up.
+pub fn frame_duration_for(base: f32, game: &Game) -> f32 {
+    let levelled = base * 0.9_f32.powi(game.level() - 1);
+    levelled.max(MIN_FRAME_DURATION.min(base)) * game.speed_factor()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// Milliseconds between two moves of the snake at level 1.
+    pub fn frame_duration(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 110.0,
+            Difficulty::Normal => 75.0,
+            Difficulty::Hard => 45.0,
+        }
+    }
+
+    pub fn frame_duration_for(&self, game: &Game) -> f32 {
+        frame_duration_for(self.frame_duration(), game)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Theme {
+    Classic,
+    Mono,
+    Neon,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Classic, Theme::Mono, Theme::Neon];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Classic => "Classic",
+            Theme::Mono => "Mono",
+            Theme::Neon => "Neon",
+        }
+    }
+
+    pub fn snake_color(&self) -> (u8, u8, u8) {
+        match self {
+            Theme::Classic => YELLOW,
+            Theme::Mono => WHITE,
+            Theme::Neon => LIME_GREEN,
+        }
+    }
+
+    pub fn opponent_color(&self) -> (u8, u8, u8) {
+        match self {
+            Theme::Classic => CYAN,
+            Theme::Mono => DARK_GREY,
+            Theme::Neon => ORANGE,
+        }
+    }
+
+    pub fn food_color(&self) -> (u8, u8, u8) {
+        match self {
+            Theme::Classic => RED,
+            Theme::Mono => GREY,
+            Theme::Neon => MAGENTA,
+        }
+    }
+
+    /// Tint applied to sprites, for games that draw on a sprite console.
+    pub fn sprite_tint(&self) -> RGBA {
+        match self {
+            Theme::Classic => RGBA::from_f32(1.0, 1.0, 1.0, 1.0),
+            Theme::Mono => RGBA::from_f32(0.7, 0.7, 0.7, 1.0),
+            Theme::Neon => RGBA::from_f32(0.6, 1.0, 0.8, 1.0),
+        }
+    }
+
+    pub fn opponent_tint(&self) -> RGBA {
+        match self {
+            Theme::Classic => RGBA::from_f32(0.6, 0.8, 1.0, 1.0),
+            Theme::Mono => RGBA::from_f32(0.4, 0.4, 0.4, 1.0),
+            Theme::Neon => RGBA::from_f32(1.0, 0.6, 0.2, 1.0),
+        }
+    }
+}
+
+/// How food turns up on the board.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FoodMode {
+    Classic,
+    FeedingFrenzy,
+}
+
+impl FoodMode {
+    pub const ALL: [FoodMode; 2] = [FoodMode::Classic, FoodMode::FeedingFrenzy];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FoodMode::Classic => "Classic",
+            FoodMode::FeedingFrenzy => "Feeding frenzy",
+        }
+    }
+
+    pub fn spawn_policy(&self) -> SpawnPolicy {
+        match self {
+            FoodMode::Classic => SpawnPolicy::CLASSIC,
+            FoodMode::FeedingFrenzy => SpawnPolicy::FEEDING_FRENZY,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControlScheme {
+    Wasd,
+    Arrows,
+    Both,
+}
+
+impl ControlScheme {
+    pub const ALL: [ControlScheme; 3] = [
+        ControlScheme::Wasd,
+        ControlScheme::Arrows,
+        ControlScheme::Both,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ControlScheme::Wasd => "WASD",
+            ControlScheme::Arrows => "Arrow keys",
+            ControlScheme::Both => "WASD + Arrow keys",
+        }
+    }
+
+    pub fn direction(&self, key: VirtualKeyCode) -> Option<Direction> {
+        let wasd = match key {
+            VirtualKeyCode::W => Some(Direction::Up),
+            VirtualKeyCode::S => Some(Direction::Down),
+            VirtualKeyCode::D => Some(Direction::Right),
+            VirtualKeyCode::A => Some(Direction::Left),
+            _ => None,
+        };
+        let arrows = match key {
+            VirtualKeyCode::Up => Some(Direction::Up),
+            VirtualKeyCode::Down => Some(Direction::Down),
+            VirtualKeyCode::Right => Some(Direction::Right),
+            VirtualKeyCode::Left => Some(Direction::Left),
+            _ => None,
+        };
+
+        match self {
+            ControlScheme::Wasd => wasd,
+            ControlScheme::Arrows => arrows,
+            ControlScheme::Both => wasd.or(arrows),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub theme: Theme,
+    pub controls: ControlScheme,
+    /// Whether special foods spawn in new games.
+    pub power_ups: bool,
+    pub food_mode: FoodMode,
+    /// Turns off flashing, particles and screen shake, for players who
+    /// find them uncomfortable.
+    pub reduced_motion: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::Normal,
+            theme: Theme::Classic,
+            controls: ControlScheme::Both,
+            power_ups: true,
+            food_mode: FoodMode::Classic,
+            reduced_motion: false,
+        }
+    }
+}
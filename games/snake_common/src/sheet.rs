@@ -0,0 +1,301 @@
+//! Builds a [`SpriteSheet`] from a grid and named regions instead of
+//! listing every sprite rectangle by hand.
+//!
+//! ```ignore
+//! let (sheet, sprites) = Sheet::new("resources/texture.png")
+//!     .grid_named(&SheetGrid::new(40, 40).with_rows(2), &["head", "tail"])
+//!     .region("logo", Rect::with_size(0, 80, 120, 40))
+//!     .build();
+//! let head = sprites.index("head");
+//! let rect = sprites.frame(head).place(feet, 0.5);
+//! ```
+
+use std::collections::HashMap;
+
+use bracket_lib::prelude::{PointF, Rect, SpriteSheet};
+
+/// A regular grid of equally sized cells. `margin` is the border around
+/// the whole grid and `padding` the gap between neighbouring cells, both
+/// in pixels. Bracket measures sprite positions from the bottom of the
+/// texture, so row 0 is the bottom row.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SheetGrid {
+    pub cell_width: i32,
+    pub cell_height: i32,
+    pub columns: i32,
+    pub rows: i32,
+    pub margin: i32,
+    pub padding: i32,
+}
+
+impl SheetGrid {
+    /// A single cell; add columns and rows with the `with_` methods.
+    pub fn new(cell_width: i32, cell_height: i32) -> Self {
+        Self {
+            cell_width,
+            cell_height,
+            columns: 1,
+            rows: 1,
+            margin: 0,
+            padding: 0,
+        }
+    }
+
+    pub fn with_columns(mut self, columns: i32) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn with_rows(mut self, rows: i32) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    pub fn with_margin(mut self, margin: i32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: i32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        (self.columns.max(0) * self.rows.max(0)) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The pixel rectangle of one cell.
+    pub fn rect(&self, column: i32, row: i32) -> Rect {
+        Rect::with_size(
+            self.margin + column * (self.cell_width + self.padding),
+            self.margin + row * (self.cell_height + self.padding),
+            self.cell_width,
+            self.cell_height,
+        )
+    }
+
+    /// Every cell, row by row from the bottom, left to right.
+    pub fn rects(&self) -> impl Iterator<Item = Rect> + '_ {
+        (0..self.rows)
+            .flat_map(move |row| (0..self.columns).map(move |column| self.rect(column, row)))
+    }
+}
+
+/// A sprite's size on the texture and the point it is placed by.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpriteFrame {
+    pub width: i32,
+    pub height: i32,
+    /// As fractions of the width and height from the top left corner, so
+    /// (0.5, 0.5) is the centre and (0.5, 1.0) the middle of the bottom
+    /// edge, where a character's feet are.
+    pub pivot: PointF,
+}
+
+impl SpriteFrame {
+    pub const CENTRE: PointF = PointF::new(0.5, 0.5);
+
+    fn new(rect: Rect, pivot: PointF) -> Self {
+        Self {
+            width: rect.width(),
+            height: rect.height(),
+            pivot,
+        }
+    }
+
+    /// The same frame for drawing mirrored left to right, which moves
+    /// the pivot to match.
+    pub fn mirrored(&self) -> Self {
+        Self {
+            pivot: PointF::new(1.0 - self.pivot.x, self.pivot.y),
+            ..*self
+        }
+    }
+
+    /// The largest scale at which the whole sprite fits in `width` by
+    /// `height` pixels without being stretched.
+    pub fn fit(&self, width: f32, height: f32) -> f32 {
+        (width / self.width.max(1) as f32).min(height / self.height.max(1) as f32)
+    }
+
+    /// Where to draw the sprite at `scale` times its size so that its
+    /// pivot lands on `at`.
+    pub fn place(&self, at: PointF, scale: f32) -> Rect {
+        let width = (self.width as f32 * scale).round();
+        let height = (self.height as f32 * scale).round();
+        Rect::with_size(
+            (at.x - self.pivot.x * width).round() as i32,
+            (at.y - self.pivot.y * height).round() as i32,
+            width as i32,
+            height as i32,
+        )
+    }
+}
+
+/// Sprite indices by name, and each sprite's frame, as handed out by
+/// [`Sheet::build`].
+#[derive(Clone, Debug, Default)]
+pub struct SpriteIndex {
+    names: HashMap<String, usize>,
+    frames: Vec<SpriteFrame>,
+}
+
+impl SpriteIndex {
+    /// The size and pivot of a sprite. Panics on an index the sheet does
+    /// not have, like [`SpriteIndex::index`] does on a name.
+    pub fn frame(&self, index: usize) -> &SpriteFrame {
+        &self.frames[index]
+    }
+
+    /// The index of a named sprite. Panics on a name the sheet was not
+    /// built with, which is a mistake in the code building it.
+    pub fn index(&self, name: &str) -> usize {
+        self.get(name)
+            .unwrap_or_else(|| panic!("no sprite named '{}'", name))
+    }
+
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// Collects sprite rectangles and their names for one texture.
+pub struct Sheet {
+    sheet: SpriteSheet,
+    index: SpriteIndex,
+}
+
+impl Sheet {
+    pub fn new<S: ToString>(path: S) -> Self {
+        Self {
+            sheet: SpriteSheet::new(path),
+            index: SpriteIndex::default(),
+        }
+    }
+
+    /// Adds every cell of `grid` as an unnamed sprite, pivoted on its
+    /// centre.
+    pub fn grid(self, grid: &SheetGrid) -> Self {
+        grid.rects().fold(self, |sheet, rect| {
+            sheet.add(None, rect, SpriteFrame::CENTRE)
+        })
+    }
+
+    /// Adds the cells of `grid` in order, naming them from `names`. Cells
+    /// past the end of `names` are left out.
+    pub fn grid_named(self, grid: &SheetGrid, names: &[&str]) -> Self {
+        grid.rects().zip(names).fold(self, |sheet, (rect, name)| {
+            sheet.add(Some(name), rect, SpriteFrame::CENTRE)
+        })
+    }
+
+    /// Adds one named sprite anywhere on the texture, pivoted on its
+    /// centre.
+    pub fn region(self, name: &str, rect: Rect) -> Self {
+        self.add(Some(name), rect, SpriteFrame::CENTRE)
+    }
+
+    /// Adds one named sprite placed by `pivot`, as in [`SpriteFrame`].
+    pub fn region_with_pivot(self, name: &str, rect: Rect, pivot: PointF) -> Self {
+        self.add(Some(name), rect, pivot)
+    }
+
+    /// Index the next sprite added will get.
+    pub fn next_index(&self) -> usize {
+        self.sheet.sprites.len()
+    }
+
+    pub fn build(self) -> (SpriteSheet, SpriteIndex) {
+        (self.sheet, self.index)
+    }
+
+    fn add(mut self, name: Option<&str>, rect: Rect, pivot: PointF) -> Self {
+        if let Some(name) = name {
+            self.index.names.insert(name.to_string(), self.next_index());
+        }
+        self.index.frames.push(SpriteFrame::new(rect, pivot));
+        self.sheet = self.sheet.add_sprite(rect);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locations(sheet: &SpriteSheet) -> Vec<(i32, i32, i32, i32)> {
+        sheet
+            .sprites
+            .iter()
+            .map(|sprite| {
+                let r = sprite.sheet_location;
+                (r.x1, r.y1, r.width(), r.height())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn slices_a_grid_with_margin_and_padding_from_the_bottom_row() {
+        let grid = SheetGrid::new(10, 6)
+            .with_columns(3)
+            .with_rows(2)
+            .with_margin(2)
+            .with_padding(1);
+        let (sheet, sprites) = Sheet::new("grid.png").grid(&grid).build();
+        assert_eq!(
+            locations(&sheet),
+            [
+                (2, 2, 10, 6),
+                (13, 2, 10, 6),
+                (24, 2, 10, 6),
+                (2, 9, 10, 6),
+                (13, 9, 10, 6),
+                (24, 9, 10, 6),
+            ]
+        );
+        assert_eq!(grid.len(), 6);
+        assert_eq!(sprites.len(), 6);
+        assert_eq!(sprites.frame(5).pivot, SpriteFrame::CENTRE);
+    }
+
+    #[test]
+    fn names_grid_cells_and_regions_in_order() {
+        let grid = SheetGrid::new(8, 8).with_columns(4);
+        let (sheet, sprites) = Sheet::new("named.png")
+            .grid_named(&grid, &["head", "tail"])
+            .region("logo", Rect::with_size(0, 8, 32, 16))
+            .build();
+        assert_eq!(
+            locations(&sheet),
+            [(0, 0, 8, 8), (8, 0, 8, 8), (0, 8, 32, 16)]
+        );
+        assert_eq!(sprites.index("head"), 0);
+        assert_eq!(sprites.index("tail"), 1);
+        assert_eq!(sprites.index("logo"), 2);
+        assert_eq!(sprites.get("body"), None);
+    }
+
+    #[test]
+    fn places_frames_by_their_pivot() {
+        let frame = SpriteFrame::new(Rect::with_size(0, 0, 10, 20), PointF::new(0.2, 1.0));
+        let rect = frame.place(PointF::new(100.0, 50.0), 2.0);
+        assert_eq!((rect.x1, rect.y1, rect.x2, rect.y2), (96, 10, 116, 50));
+
+        let rect = frame.mirrored().place(PointF::new(100.0, 50.0), 2.0);
+        assert_eq!((rect.x1, rect.x2), (84, 104));
+        assert_eq!(frame.fit(40.0, 40.0), 2.0);
+    }
+}
@@ -0,0 +1,834 @@
+use bracket_lib::prelude::{DistanceAlg, Point, RandomNumberGenerator};
+
+use crate::Direction;
+
+/// Foods to eat before the snake speeds up by a level.
+pub const FOODS_PER_LEVEL: i32 = 5;
+/// How long a power-up lasts once eaten, in ticks.
+pub const EFFECT_TICKS: u32 = 60;
+/// Ticks before it disappears that a food counts as expiring.
+const EXPIRY_WARNING_TICKS: u32 = 15;
+/// Segments a shrink food takes off the tail.
+const SHRINK_SEGMENTS: usize = 3;
+/// Random cells tried for a new food before scanning the whole board.
+const SPAWN_TRIES: usize = 32;
+
+/// Board size and starting conditions. Together with a seed this fully
+/// determines a run, given the same inputs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GameConfig {
+    pub width: i32,
+    pub height: i32,
+    pub initial_length: i32,
+    /// Adds a second, computer controlled snake to the board.
+    pub opponent: bool,
+    /// Lets special foods spawn next to the normal one, see [`FoodKind`].
+    pub power_ups: bool,
+    pub spawn: SpawnPolicy,
+}
+
+impl GameConfig {
+    /// Longest side a board can have.
+    pub const MAX_SIDE: i32 = 1000;
+
+    /// Checks the board has cells and room for the snake. It starts a fifth
+    /// of the way across, heading right, with its body trailing off to the
+    /// left; an opponent mirrors it from the right edge.
+    pub fn validate(&self) -> Result<(), String> {
+        let sides = 1..=Self::MAX_SIDE;
+        if !sides.contains(&self.width) || !sides.contains(&self.height) {
+            return Err(format!(
+                "board {}x{} needs sides from 1 to {}",
+                self.width,
+                self.height,
+                Self::MAX_SIDE
+            ));
+        }
+        let longest = self.width / 5 + 1;
+        if self.initial_length < 1 || self.initial_length > longest {
+            return Err(format!(
+                "initial length {} doesn't fit a board {} wide, which takes 1 to {}",
+                self.initial_length, self.width, longest
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How many foods are on the board and when they come and go.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SpawnPolicy {
+    /// Most foods on the board at once. The board starts out full.
+    pub max_foods: usize,
+    /// Ticks between new foods appearing while there are fewer than
+    /// `max_foods`. With 0, an eaten food is replaced straight away.
+    pub interval: u32,
+    /// Foods spawn at least this many cells (Manhattan) away from the
+    /// player's head, when the board has room for it.
+    pub min_distance: i32,
+    /// Ticks any food stays before it is removed, on top of the lifetime
+    /// of special foods. `None` keeps normal food around until eaten.
+    pub lifetime: Option<u32>,
+}
+
+impl SpawnPolicy {
+    /// One food at a time, replaced as soon as it is eaten.
+    pub const CLASSIC: SpawnPolicy = SpawnPolicy {
+        max_foods: 1,
+        interval: 0,
+        min_distance: 0,
+        lifetime: None,
+    };
+
+    /// A crowded board of short-lived food that keeps topping itself up.
+    pub const FEEDING_FRENZY: SpawnPolicy = SpawnPolicy {
+        max_foods: 8,
+        interval: 6,
+        min_distance: 4,
+        lifetime: Some(120),
+    };
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FoodKind {
+    Normal,
+    /// Worth several points.
+    Golden,
+    SlowDown,
+    SpeedUp,
+    /// Takes a few segments off the tail instead of adding one.
+    Shrink,
+    /// Lets the snake pass through its own body for a while.
+    Ghost,
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 6] = [
+        FoodKind::Normal,
+        FoodKind::Golden,
+        FoodKind::SlowDown,
+        FoodKind::SpeedUp,
+        FoodKind::Shrink,
+        FoodKind::Ghost,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FoodKind::Normal => "Food",
+            FoodKind::Golden => "Golden",
+            FoodKind::SlowDown => "Slow",
+            FoodKind::SpeedUp => "Fast",
+            FoodKind::Shrink => "Shrink",
+            FoodKind::Ghost => "Ghost",
+        }
+    }
+
+    /// Relative chance of this kind being picked when food spawns.
+    pub fn spawn_weight(&self) -> i32 {
+        match self {
+            FoodKind::Normal => 70,
+            FoodKind::Golden => 8,
+            FoodKind::SlowDown => 6,
+            FoodKind::SpeedUp => 6,
+            FoodKind::Shrink => 5,
+            FoodKind::Ghost => 5,
+        }
+    }
+
+    pub fn points(&self) -> i32 {
+        match self {
+            FoodKind::Golden => 5,
+            _ => 1,
+        }
+    }
+
+    /// Ticks an uneaten food stays on the board before it is replaced.
+    /// Normal food never goes away.
+    pub fn lifetime(&self) -> Option<u32> {
+        match self {
+            FoodKind::Normal => None,
+            FoodKind::Golden => Some(50),
+            _ => Some(80),
+        }
+    }
+
+    /// The lasting effect of eating this food, if any.
+    pub fn effect(&self) -> Option<Effect> {
+        match self {
+            FoodKind::SlowDown => Some(Effect::SlowDown),
+            FoodKind::SpeedUp => Some(Effect::SpeedUp),
+            FoodKind::Ghost => Some(Effect::Ghost),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Food {
+    pub cell: Point,
+    pub kind: FoodKind,
+    /// Tick on which the food disappears if nobody has eaten it.
+    pub expires_at: Option<u32>,
+}
+
+impl Food {
+    /// Whether the food is close to disappearing at tick `now`, for
+    /// renderers to make it blink.
+    pub fn is_expiring(&self, now: u32) -> bool {
+        self.expires_at
+            .is_some_and(|tick| tick.saturating_sub(now) < EXPIRY_WARNING_TICKS)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Effect {
+    SlowDown,
+    SpeedUp,
+    Ghost,
+}
+
+impl Effect {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Effect::SlowDown => "Slow",
+            Effect::SpeedUp => "Fast",
+            Effect::Ghost => "Ghost",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ActiveEffect {
+    pub effect: Effect,
+    /// First tick on which the effect no longer applies.
+    pub until: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeathCause {
+    Wall,
+    SelfCollision,
+    Opponent,
+    GaveUp,
+}
+
+impl DeathCause {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            DeathCause::Wall => "You crashed into a wall",
+            DeathCause::SelfCollision => "You bit your own tail",
+            DeathCause::Opponent => "You ran into your opponent",
+            DeathCause::GaveUp => "You gave up",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Death {
+    pub cause: DeathCause,
+    /// The cell the head tried to move into. May lie outside the board.
+    pub cell: Point,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SnakeId {
+    Player,
+    Opponent,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepEvent {
+    Moved,
+    Ate,
+    Died(Death),
+}
+
+#[derive(Clone, Debug)]
+pub struct Snake {
+    /// Cells from head to tail.
+    pub body: Vec<Point>,
+    pub direction: Direction,
+}
+
+impl Snake {
+    fn new(head: Point, length: i32, direction: Direction) -> Self {
+        let behind = direction.opposite().delta();
+        Self {
+            body: (0..length.max(1))
+                .map(|i| Point::new(head.x + behind.x * i, head.y + behind.y * i))
+                .collect(),
+            direction,
+        }
+    }
+
+    fn turn(&mut self, input: Option<Direction>) {
+        if let Some(direction) = input {
+            if self.length() == 1 || direction != self.direction.opposite() {
+                self.direction = direction;
+            }
+        }
+    }
+
+    fn next_head(&self) -> Point {
+        self.head() + self.direction.delta()
+    }
+
+    /// Cells that will still be occupied after the next move.
+    fn solid_cells(&self, growing: bool) -> &[Point] {
+        if growing {
+            &self.body[..]
+        } else {
+            &self.body[..self.length() - 1]
+        }
+    }
+
+    pub fn head(&self) -> Point {
+        self.body[0]
+    }
+
+    pub fn length(&self) -> usize {
+        self.body.len()
+    }
+
+    pub fn occupies(&self, cell: Point) -> bool {
+        self.body.contains(&cell)
+    }
+}
+
+/// The rules of snake on a grid, with no rendering or timing attached.
+/// Call [`Game::step`] once per tick.
+#[derive(Clone)]
+pub struct Game {
+    pub config: GameConfig,
+    pub seed: u64,
+    pub snake: Snake,
+    pub opponent: Option<Snake>,
+    pub foods: Vec<Food>,
+    pub effects: Vec<ActiveEffect>,
+    pub score: i32,
+    pub opponent_score: i32,
+    pub foods_eaten: i32,
+    pub ticks: u32,
+    pub death: Option<Death>,
+    rng: RandomNumberGenerator,
+}
+
+impl Game {
+    pub fn new(config: GameConfig, seed: u64) -> Self {
+        let head = Point::new(config.width / 5, config.height / 2);
+        let opponent_head = Point::new(config.width - 1 - config.width / 5, config.height / 2);
+        let mut game = Self {
+            config,
+            seed,
+            snake: Snake::new(head, config.initial_length, Direction::Right),
+            opponent: config
+                .opponent
+                .then(|| Snake::new(opponent_head, config.initial_length, Direction::Left)),
+            foods: Vec::new(),
+            effects: Vec::new(),
+            score: 0,
+            opponent_score: 0,
+            foods_eaten: 0,
+            ticks: 0,
+            death: None,
+            rng: RandomNumberGenerator::seeded(seed),
+        };
+        for _ in 0..config.spawn.max_foods.max(1) {
+            game.spawn_food();
+        }
+        game
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.death.is_some()
+    }
+
+    /// True once there is no free cell left to put food on.
+    pub fn is_complete(&self) -> bool {
+        self.foods.is_empty()
+    }
+
+    pub fn food_at(&self, cell: Point) -> Option<&Food> {
+        self.foods.iter().find(|food| food.cell == cell)
+    }
+
+    /// The food closest to `from`, as the snake moves.
+    pub fn nearest_food(&self, from: Point) -> Option<&Food> {
+        self.foods
+            .iter()
+            .min_by_key(|food| (food.cell.x - from.x).abs() + (food.cell.y - from.y).abs())
+    }
+
+    /// Speed level, starting at 1 and going up every
+    /// [`FOODS_PER_LEVEL`] foods.
+    pub fn level(&self) -> i32 {
+        1 + self.foods_eaten / FOODS_PER_LEVEL
+    }
+
+    pub fn has_effect(&self, effect: Effect) -> bool {
+        self.effects.iter().any(|active| active.effect == effect)
+    }
+
+    /// Ticks left on `effect`, or 0 when it isn't active.
+    pub fn effect_remaining(&self, effect: Effect) -> u32 {
+        self.effects
+            .iter()
+            .find(|active| active.effect == effect)
+            .map_or(0, |active| active.until.saturating_sub(self.ticks))
+    }
+
+    /// How much longer than usual each move takes because of power-ups.
+    pub fn speed_factor(&self) -> f32 {
+        if self.has_effect(Effect::SlowDown) {
+            1.5
+        } else if self.has_effect(Effect::SpeedUp) {
+            0.6
+        } else {
+            1.0
+        }
+    }
+
+    pub fn in_bounds(&self, cell: Point) -> bool {
+        cell.x >= 0 && cell.x < self.config.width && cell.y >= 0 && cell.y < self.config.height
+    }
+
+    pub fn snake_by_id(&self, id: SnakeId) -> Option<&Snake> {
+        match id {
+            SnakeId::Player => Some(&self.snake),
+            SnakeId::Opponent => self.opponent.as_ref(),
+        }
+    }
+
+    /// Advances the game by one tick, turning first if `input` is a legal
+    /// turn. Reversing onto the neck is ignored.
+    pub fn step(&mut self, input: Option<Direction>) -> StepEvent {
+        self.step_versus(input, None)
+    }
+
+    /// Like [`Game::step`], also moving the opponent if there is one. The
+    /// player loses any collision with the opponent, head-on included; an
+    /// opponent that crashes is simply removed from the board.
+    pub fn step_versus(
+        &mut self,
+        input: Option<Direction>,
+        opponent_input: Option<Direction>,
+    ) -> StepEvent {
+        if let Some(death) = self.death {
+            return StepEvent::Died(death);
+        }
+
+        self.snake.turn(input);
+        if let Some(opponent) = self.opponent.as_mut() {
+            opponent.turn(opponent_input);
+        }
+
+        self.ticks += 1;
+        let ticks = self.ticks;
+        self.effects.retain(|active| active.until > ticks);
+
+        let next = self.snake.next_head();
+        let eaten = self.food_at(next).map(|food| food.kind);
+        let grows = eaten.is_some_and(|kind| kind != FoodKind::Shrink);
+        let opponent_next = self.opponent.as_ref().map(|opponent| opponent.next_head());
+        let opponent_eats =
+            opponent_next.is_some_and(|cell| cell != next && self.food_at(cell).is_some());
+
+        if !self.in_bounds(next) {
+            return self.die(DeathCause::Wall, next);
+        }
+        if !self.has_effect(Effect::Ghost) && self.snake.solid_cells(grows).contains(&next) {
+            return self.die(DeathCause::SelfCollision, next);
+        }
+        if let Some(opponent) = &self.opponent {
+            if opponent_next == Some(next) || opponent.solid_cells(opponent_eats).contains(&next) {
+                return self.die(DeathCause::Opponent, next);
+            }
+        }
+
+        if let (Some(opponent), Some(opponent_next)) = (&self.opponent, opponent_next) {
+            if !self.in_bounds(opponent_next)
+                || opponent.solid_cells(opponent_eats).contains(&opponent_next)
+                || self.snake.solid_cells(grows).contains(&opponent_next)
+            {
+                self.opponent = None;
+            }
+        }
+
+        self.snake.body.insert(0, next);
+        if !grows {
+            self.snake.body.pop();
+        }
+
+        if let (Some(opponent), Some(opponent_next)) = (self.opponent.as_mut(), opponent_next) {
+            opponent.body.insert(0, opponent_next);
+            if opponent_eats {
+                let points = self
+                    .food_at(opponent_next)
+                    .map_or(1, |food| food.kind.points());
+                self.opponent_score += points;
+                self.foods.retain(|food| food.cell != opponent_next);
+            } else {
+                opponent.body.pop();
+            }
+        }
+
+        if let Some(kind) = eaten {
+            self.foods.retain(|food| food.cell != next);
+            self.score += kind.points();
+            self.foods_eaten += 1;
+            if kind == FoodKind::Shrink {
+                let keep = self.snake.length().saturating_sub(SHRINK_SEGMENTS).max(1);
+                self.snake.body.truncate(keep);
+            }
+            if let Some(effect) = kind.effect() {
+                self.start_effect(effect);
+            }
+        }
+
+        self.foods
+            .retain(|food| food.expires_at.is_none_or(|tick| ticks < tick));
+        self.refill_foods();
+
+        if eaten.is_some() {
+            StepEvent::Ate
+        } else {
+            StepEvent::Moved
+        }
+    }
+
+    /// Starts or restarts `effect`. Slowing down and speeding up cancel
+    /// each other out.
+    fn start_effect(&mut self, effect: Effect) {
+        self.effects.retain(|active| {
+            active.effect != effect
+                && !matches!(
+                    (active.effect, effect),
+                    (Effect::SlowDown, Effect::SpeedUp) | (Effect::SpeedUp, Effect::SlowDown)
+                )
+        });
+        self.effects.push(ActiveEffect {
+            effect,
+            until: self.ticks + EFFECT_TICKS,
+        });
+    }
+
+    pub fn resign(&mut self) {
+        if self.death.is_none() {
+            self.death = Some(Death {
+                cause: DeathCause::GaveUp,
+                cell: self.snake.head(),
+            });
+        }
+    }
+
+    fn die(&mut self, cause: DeathCause, cell: Point) -> StepEvent {
+        let death = Death { cause, cell };
+        self.death = Some(death);
+        StepEvent::Died(death)
+    }
+
+    /// Tops the board back up according to the spawn policy, and makes
+    /// sure there is always at least one food to go for.
+    fn refill_foods(&mut self) {
+        let policy = self.config.spawn;
+        let max_foods = policy.max_foods.max(1);
+        if policy.interval == 0 {
+            while self.foods.len() < max_foods && self.spawn_food() {}
+        } else if self.ticks.is_multiple_of(policy.interval) && self.foods.len() < max_foods {
+            self.spawn_food();
+        }
+        if self.foods.is_empty() {
+            self.spawn_food();
+        }
+    }
+
+    /// Puts a food on a random free cell, returning false when there is
+    /// no free cell left.
+    fn spawn_food(&mut self) -> bool {
+        let Some(cell) = self.random_food_cell().or_else(|| self.scan_food_cell()) else {
+            return false;
+        };
+        let kind = if self.config.power_ups {
+            self.random_food_kind()
+        } else {
+            FoodKind::Normal
+        };
+        let lifetime = match (self.config.spawn.lifetime, kind.lifetime()) {
+            (Some(policy), Some(own)) => Some(policy.min(own)),
+            (policy, own) => policy.or(own),
+        };
+
+        self.foods.push(Food {
+            cell,
+            kind,
+            expires_at: lifetime.map(|lifetime| self.ticks + lifetime),
+        });
+        true
+    }
+
+    fn is_free(&self, cell: Point) -> bool {
+        !self.snake.occupies(cell)
+            && !self.opponent.as_ref().is_some_and(|o| o.occupies(cell))
+            && self.food_at(cell).is_none()
+    }
+
+    fn far_enough(&self, cell: Point) -> bool {
+        let min_distance = self.config.spawn.min_distance as f32;
+        DistanceAlg::Manhattan.distance2d(self.snake.head(), cell) >= min_distance
+    }
+
+    /// Tries a few random cells, which finds a spot straight away unless
+    /// the board is nearly full.
+    fn random_food_cell(&mut self) -> Option<Point> {
+        for _ in 0..SPAWN_TRIES {
+            let cell = Point::new(
+                self.rng.range(0, self.config.width),
+                self.rng.range(0, self.config.height),
+            );
+            if self.is_free(cell) && self.far_enough(cell) {
+                return Some(cell);
+            }
+        }
+        None
+    }
+
+    /// Picks from every free cell, preferring ones far enough from the head.
+    fn scan_food_cell(&mut self) -> Option<Point> {
+        let free: Vec<Point> = (0..self.config.height)
+            .flat_map(|y| (0..self.config.width).map(move |x| Point::new(x, y)))
+            .filter(|cell| self.is_free(*cell))
+            .collect();
+        let away: Vec<Point> = free
+            .iter()
+            .copied()
+            .filter(|cell| self.far_enough(*cell))
+            .collect();
+        let candidates = if away.is_empty() { &free } else { &away };
+        self.rng.random_slice_entry(candidates).copied()
+    }
+
+    fn random_food_kind(&mut self) -> FoodKind {
+        let total: i32 = FoodKind::ALL.iter().map(|kind| kind.spawn_weight()).sum();
+        let mut roll = self.rng.range(0, total);
+        for kind in FoodKind::ALL {
+            if roll < kind.spawn_weight() {
+                return kind;
+            }
+            roll -= kind.spawn_weight();
+        }
+        FoodKind::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(spawn: SpawnPolicy) -> GameConfig {
+        GameConfig {
+            width: 100,
+            height: 20,
+            initial_length: 3,
+            opponent: false,
+            power_ups: false,
+            spawn,
+        }
+    }
+
+    /// Puts a food of `kind` where the snake is about to move, and no
+    /// other food on the board.
+    fn feed(game: &mut Game, kind: FoodKind) {
+        game.foods = vec![Food {
+            cell: game.snake.next_head(),
+            kind,
+            expires_at: None,
+        }];
+    }
+
+    #[test]
+    fn golden_food_is_worth_more() {
+        let mut game = Game::new(config(SpawnPolicy::CLASSIC), 1);
+        feed(&mut game, FoodKind::Golden);
+        assert_eq!(game.step(None), StepEvent::Ate);
+        assert_eq!(game.score, FoodKind::Golden.points());
+        assert_eq!(game.foods_eaten, 1);
+        assert_eq!(game.snake.length(), 4);
+    }
+
+    #[test]
+    fn shrink_keeps_at_least_the_head() {
+        let mut game = Game::new(config(SpawnPolicy::CLASSIC), 1);
+        game.snake.body.truncate(2);
+        feed(&mut game, FoodKind::Shrink);
+        game.step(None);
+        assert_eq!(game.snake.length(), 1);
+
+        let mut game = Game::new(
+            GameConfig {
+                initial_length: 6,
+                ..config(SpawnPolicy::CLASSIC)
+            },
+            1,
+        );
+        feed(&mut game, FoodKind::Shrink);
+        game.step(None);
+        assert_eq!(game.snake.length(), 6 - SHRINK_SEGMENTS);
+    }
+
+    #[test]
+    fn ghost_passes_through_own_body() {
+        // a coil whose head is about to turn into its own neck
+        let coil = vec![
+            Point::new(5, 5),
+            Point::new(6, 5),
+            Point::new(6, 4),
+            Point::new(5, 4),
+            Point::new(4, 4),
+        ];
+        let mut game = Game::new(config(SpawnPolicy::CLASSIC), 1);
+        game.foods.clear();
+        game.snake.body = coil.clone();
+        game.snake.direction = Direction::Left;
+        assert!(matches!(
+            game.step(Some(Direction::Up)),
+            StepEvent::Died(Death {
+                cause: DeathCause::SelfCollision,
+                ..
+            })
+        ));
+
+        let mut game = Game::new(config(SpawnPolicy::CLASSIC), 1);
+        game.foods.clear();
+        game.snake.body = coil;
+        game.snake.direction = Direction::Left;
+        game.start_effect(Effect::Ghost);
+        assert_eq!(game.step(Some(Direction::Up)), StepEvent::Moved);
+        assert_eq!(game.snake.head(), Point::new(5, 4));
+    }
+
+    #[test]
+    fn slow_and_speed_cancel_out() {
+        let mut game = Game::new(config(SpawnPolicy::CLASSIC), 1);
+        feed(&mut game, FoodKind::SlowDown);
+        game.step(None);
+        assert!(game.has_effect(Effect::SlowDown));
+        assert_eq!(game.speed_factor(), 1.5);
+
+        feed(&mut game, FoodKind::SpeedUp);
+        game.step(None);
+        assert!(!game.has_effect(Effect::SlowDown));
+        assert!(game.has_effect(Effect::SpeedUp));
+        assert_eq!(game.effects.len(), 1);
+        assert_eq!(game.speed_factor(), 0.6);
+    }
+
+    #[test]
+    fn effects_expire() {
+        let mut game = Game::new(config(SpawnPolicy::CLASSIC), 1);
+        feed(&mut game, FoodKind::Ghost);
+        game.step(None);
+        let eaten_at = game.ticks;
+        assert_eq!(game.effect_remaining(Effect::Ghost), EFFECT_TICKS);
+
+        while game.ticks < eaten_at + EFFECT_TICKS - 1 {
+            game.foods.clear();
+            game.step(None);
+            assert!(
+                game.has_effect(Effect::Ghost),
+                "gone at tick {}",
+                game.ticks
+            );
+        }
+        game.step(None);
+        assert!(!game.has_effect(Effect::Ghost));
+        assert!(!game.is_over());
+    }
+
+    #[test]
+    fn special_food_disappears() {
+        let mut game = Game::new(config(SpawnPolicy::CLASSIC), 1);
+        // somewhere the snake won't get to first
+        let corner = Point::new(99, 19);
+        let lifetime = FoodKind::Golden.lifetime().unwrap();
+        game.foods = vec![Food {
+            cell: corner,
+            kind: FoodKind::Golden,
+            expires_at: Some(lifetime),
+        }];
+
+        for _ in 1..lifetime {
+            game.step(None);
+            assert!(
+                game.food_at(corner).is_some(),
+                "gone at tick {}",
+                game.ticks
+            );
+        }
+        game.step(None);
+        assert!(game.food_at(corner).is_none());
+        assert_eq!(game.foods.len(), 1, "a new food takes its place");
+    }
+
+    #[test]
+    fn timed_food_disappears() {
+        let policy = SpawnPolicy {
+            lifetime: Some(10),
+            ..SpawnPolicy::CLASSIC
+        };
+        let mut game = Game::new(config(policy), 1);
+        assert_eq!(game.foods[0].expires_at, Some(10));
+        // somewhere the snake won't get to first
+        let corner = Point::new(99, 19);
+        game.foods[0].cell = corner;
+
+        for _ in 0..9 {
+            game.step(None);
+            assert!(
+                game.food_at(corner).is_some(),
+                "gone at tick {}",
+                game.ticks
+            );
+        }
+        game.step(None);
+        assert!(game.food_at(corner).is_none());
+        assert_eq!(game.foods.len(), 1, "a new food takes its place");
+        assert_eq!(game.foods[0].expires_at, Some(20));
+    }
+
+    #[test]
+    fn feeding_frenzy_spawns_by_weight() {
+        let config = GameConfig {
+            power_ups: true,
+            ..config(SpawnPolicy::FEEDING_FRENZY)
+        };
+        let mut counts = [0; FoodKind::ALL.len()];
+        for seed in 0..500 {
+            let game = Game::new(config, seed);
+            assert_eq!(game.foods.len(), SpawnPolicy::FEEDING_FRENZY.max_foods);
+            for food in &game.foods {
+                let distance = DistanceAlg::Manhattan.distance2d(game.snake.head(), food.cell);
+                assert!(distance >= SpawnPolicy::FEEDING_FRENZY.min_distance as f32);
+                let kind = FoodKind::ALL.iter().position(|kind| *kind == food.kind);
+                counts[kind.unwrap()] += 1;
+            }
+        }
+
+        let total: i32 = counts.iter().sum();
+        let weights: i32 = FoodKind::ALL.iter().map(|kind| kind.spawn_weight()).sum();
+        for (kind, count) in FoodKind::ALL.iter().zip(counts) {
+            let share = count as f32 / total as f32;
+            let expected = kind.spawn_weight() as f32 / weights as f32;
+            assert!(
+                (share - expected).abs() < 0.03,
+                "{:?}: {} of foods, expected {}",
+                kind,
+                share,
+                expected
+            );
+        }
+
+        // the same seed always lays out the same board
+        let foods = |seed| Game::new(config, seed).foods;
+        assert_eq!(foods(42), foods(42));
+    }
+}
@@ -0,0 +1,35 @@
+//! Small text files that survive between runs: plain files on desktop,
+//! entries in the browser's local storage on the web, keyed by the same
+//! name.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(name: &str) -> Result<String, String> {
+    std::fs::read_to_string(name).map_err(|err| err.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(name: &str, contents: &str) -> Result<(), String> {
+    std::fs::write(name, contents).map_err(|err| err.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| "local storage is not available".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(name: &str) -> Result<String, String> {
+    local_storage()?
+        .get_item(name)
+        .map_err(|_| "could not read local storage".to_string())?
+        .ok_or_else(|| "not found".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(name: &str, contents: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(name, contents)
+        .map_err(|_| "local storage is full or disabled".to_string())
+}
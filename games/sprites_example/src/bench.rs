@@ -0,0 +1,159 @@
+//! Timing for the stress mode: a clock that works on desktop and the
+//! web, smoothed per-frame timings for the HUD, and a benchmark that
+//! steps through dood counts and appends its results to a CSV file.
+
+use snake_common::storage;
+
+/// Where benchmark results go, one row per dood count per run.
+pub const RESULTS: &str = "sprites_bench.csv";
+const HEADER: &str = "label,doods,fps,frame_ms,update_ms,submit_ms";
+
+/// Dood counts a benchmark run goes through, in order.
+const SCHEDULE: [usize; 6] = [100, 1_000, 5_000, 10_000, 25_000, 50_000];
+/// Time given to each count to settle before measuring it.
+const WARMUP_MS: f32 = 1000.0;
+const MEASURE_MS: f32 = 3000.0;
+
+/// Milliseconds since some fixed point, for measuring spans within a frame.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now())
+}
+
+/// How long one frame took, and the parts of it measured separately.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Timings {
+    pub frame_ms: f32,
+    /// Steering and animation.
+    pub update_ms: f32,
+    /// Handing sprites to the console; drawing them happens after `tick`.
+    pub submit_ms: f32,
+}
+
+impl Timings {
+    /// Eases towards `latest`, so the HUD can be read while it changes.
+    pub fn smooth(&mut self, latest: Timings) {
+        const RATE: f32 = 0.05;
+        self.frame_ms += (latest.frame_ms - self.frame_ms) * RATE;
+        self.update_ms += (latest.update_ms - self.update_ms) * RATE;
+        self.submit_ms += (latest.submit_ms - self.submit_ms) * RATE;
+    }
+}
+
+struct Row {
+    doods: usize,
+    frames: u32,
+    total: Timings,
+}
+
+pub struct Bench {
+    label: String,
+    stage: usize,
+    stage_ms: f32,
+    frames: u32,
+    total: Timings,
+    rows: Vec<Row>,
+}
+
+impl Bench {
+    /// `label` names the run in the results, such as the bracket-lib
+    /// version being tried.
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            stage: 0,
+            stage_ms: 0.0,
+            frames: 0,
+            total: Timings::default(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// How many doods the current stage wants.
+    pub fn doods(&self) -> usize {
+        SCHEDULE[self.stage.min(SCHEDULE.len() - 1)]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.stage >= SCHEDULE.len()
+    }
+
+    pub fn progress(&self) -> String {
+        format!(
+            "Benchmark {}/{}: {} doods",
+            (self.stage + 1).min(SCHEDULE.len()),
+            SCHEDULE.len(),
+            self.doods()
+        )
+    }
+
+    /// Counts one frame, moving on to the next stage once this one has
+    /// been measured for long enough.
+    pub fn record(&mut self, timings: Timings) {
+        if self.is_finished() {
+            return;
+        }
+        self.stage_ms += timings.frame_ms;
+        if self.stage_ms > WARMUP_MS {
+            self.frames += 1;
+            self.total.frame_ms += timings.frame_ms;
+            self.total.update_ms += timings.update_ms;
+            self.total.submit_ms += timings.submit_ms;
+        }
+        if self.stage_ms > WARMUP_MS + MEASURE_MS {
+            self.rows.push(Row {
+                doods: self.doods(),
+                frames: self.frames,
+                total: self.total,
+            });
+            self.stage += 1;
+            self.stage_ms = 0.0;
+            self.frames = 0;
+            self.total = Timings::default();
+        }
+    }
+
+    /// The results as CSV rows, without the header.
+    pub fn csv(&self) -> String {
+        self.rows
+            .iter()
+            .map(|row| {
+                let frames = row.frames.max(1) as f32;
+                let frame_ms = row.total.frame_ms / frames;
+                format!(
+                    "{},{},{:.1},{:.3},{:.3},{:.3}\n",
+                    self.label.replace(',', " "),
+                    row.doods,
+                    1000.0 / frame_ms.max(f32::EPSILON),
+                    frame_ms,
+                    row.total.update_ms / frames,
+                    row.total.submit_ms / frames,
+                )
+            })
+            .collect()
+    }
+
+    /// Adds the results to the end of [`RESULTS`], starting it with a
+    /// header if it is new.
+    pub fn save(&self) -> Result<(), String> {
+        let mut contents = storage::read(RESULTS).unwrap_or_default();
+        if contents.trim().is_empty() {
+            contents = format!("{}\n", HEADER);
+        } else if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push_str(&self.csv());
+        storage::write(RESULTS, &contents)
+    }
+}
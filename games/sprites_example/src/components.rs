@@ -0,0 +1,81 @@
+//! The data a dood is made of. Components hold state only; everything
+//! that changes it lives in a system.
+
+use bevy_ecs::prelude::*;
+use bracket_lib::prelude::*;
+use snake_common::clips::Animator;
+
+/// Marks the walking characters, as opposed to anything else that might
+/// be added to the world later.
+#[derive(Component)]
+pub struct Dood;
+
+/// Where an entity stands, in sprite console pixels. For anything drawn
+/// this is where its sprite's pivot goes: a dood's feet.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Position(pub PointF);
+
+/// Pixels per second.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Velocity(pub PointF);
+
+/// Steering state that has to carry over between frames.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Wander {
+    /// Where on its wander circle the entity is heading, in radians.
+    pub angle: f32,
+}
+
+/// What to draw at an entity's position.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Sprite {
+    pub index: usize,
+    pub tint: RGBA,
+    /// Breaks ties with other sprites on the same row, as in
+    /// [`Layer::y_sorted`](snake_common::layers::Layer::y_sorted).
+    pub rank: u8,
+    /// Mirrored left to right. The dood frames face right, so this is
+    /// how they face left.
+    pub flip: bool,
+}
+
+/// Picks an entity's sprite from a clip as time goes by.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Animation {
+    pub animator: Animator,
+    /// How quickly the clip plays at full speed; the actual rate follows
+    /// how fast the entity is moving.
+    pub pace: f32,
+}
+
+/// Picked by the player, to be told where to walk.
+#[derive(Component)]
+pub struct Selected;
+
+/// Held by the mouse: follows the pointer instead of moving itself.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Dragged {
+    /// From the pointer to the entity's position, so it doesn't jump to
+    /// put its feet under the pointer.
+    pub offset: PointF,
+}
+
+/// A route across the tile map that the entity walks instead of
+/// steering, removed once it arrives.
+#[derive(Component, Clone, Debug)]
+pub struct Path {
+    pub waypoints: Vec<PointF>,
+    /// The waypoint being walked to.
+    pub next: usize,
+}
+
+/// Everything a dood is spawned with.
+#[derive(Bundle)]
+pub struct DoodBundle {
+    pub dood: Dood,
+    pub position: Position,
+    pub velocity: Velocity,
+    pub wander: Wander,
+    pub sprite: Sprite,
+    pub animation: Animation,
+}
@@ -1,94 +1,650 @@
-use bracket_lib::prelude::*;
+mod bench;
+mod components;
+mod map;
+mod picking;
+mod resources;
+mod spatial;
+mod steering;
+mod systems;
 
+use std::path::PathBuf;
 
+use bench::{now_ms, Bench, Timings};
+use bevy_ecs::prelude::*;
+use bracket_lib::prelude::*;
+use components::{
+    Animation, Dood, DoodBundle, Dragged, Path, Position, Selected, Sprite, Velocity, Wander,
+};
+use map::TileMap;
+use picking::MouseButtons;
+use resources::{Cursor, DrawList, FrameTime, Library, Rng, Sprites, Target};
+use snake_common::aseprite;
+use snake_common::assets::{self, Asset};
+use snake_common::clips::{Animator, Clips};
+use steering::{Goal, Steering, Weights};
 
 add_wasm_support!();
 
-struct Dood {
-    x: i32,
-    y: i32,
-}
+const SHEET: &str = include_str!("../resources/sprite_dood.json");
+const CLIPS: &str = include_str!("../resources/doods.anim");
+const MAP: &str = include_str!("../resources/obstacles.map");
+const WIDTH: i32 = 640;
+const HEIGHT: i32 = 400;
+/// Edge length of a map tile in pixels, two text characters across.
+const TILE_SIZE: f32 = 16.0;
+
+/// The map goes under the sprites and the HUD over them.
+const MAP_CONSOLE: usize = 0;
+const SPRITE_CONSOLE: usize = 1;
+const HUD_CONSOLE: usize = 2;
+/// How tall a dood stands on screen at `--scale 1`, in pixels. Its
+/// width follows from the sprite's own shape.
+const DOOD_HEIGHT: f32 = 40.0;
+const MAX_DOODS: usize = 50_000;
 
 struct State {
-    frame: usize,
-    timer: f32,
-    doods: Vec<Dood>,
-    rng: RandomNumberGenerator,
+    world: World,
+    /// Steering, movement and animation.
+    update: Schedule,
+    /// Lays out the sprites for [`DrawList`].
+    render: Schedule,
+    /// Whether the doods run to the mouse pointer rather than from it.
+    seek_pointer: bool,
+    mouse: MouseButtons,
+    /// The map only needs drawing once; its console is never cleared.
+    map_drawn: bool,
+    timings: Timings,
+    /// Set while a `--bench` run steps through its dood counts.
+    bench: Option<Bench>,
+}
+
+impl State {
+    fn count(&mut self) -> usize {
+        self.world
+            .query_filtered::<(), With<Dood>>()
+            .iter(&self.world)
+            .count()
+    }
+
+    /// Adds or removes doods until there are `count` of them.
+    fn set_count(&mut self, count: usize) {
+        let count = count.clamp(1, MAX_DOODS);
+        let doods: Vec<Entity> = self
+            .world
+            .query_filtered::<Entity, With<Dood>>()
+            .iter(&self.world)
+            .collect();
+        for entity in doods.iter().skip(count) {
+            self.world.despawn(*entity);
+        }
+        if doods.len() < count {
+            let new = new_doods(&mut self.world, count - doods.len());
+            self.world.spawn_batch(new);
+        }
+    }
+
+    /// Left click selects the dood under the pointer and picks it up
+    /// until the button is let go, or with Ctrl adds it to the selection.
+    /// Clicking open ground sends the selection there. Right click
+    /// removes a dood, or adds a new one if there is none.
+    fn handle_mouse(&mut self, pointer: PointF) {
+        self.mouse.update();
+        self.world.insert_resource(Cursor(pointer));
+        // keys may have removed doods since the draw list was made
+        let hit = picking::pick(self.world.resource::<DrawList>(), pointer)
+            .filter(|entity| self.world.get_entity(*entity).is_ok());
+
+        if self.mouse.pressed(picking::LEFT) {
+            match hit {
+                Some(entity) if self.mouse.ctrl => {
+                    let mut dood = self.world.entity_mut(entity);
+                    if dood.contains::<Selected>() {
+                        dood.remove::<Selected>();
+                    } else {
+                        dood.insert(Selected);
+                    }
+                }
+                Some(entity) => {
+                    self.clear_selection();
+                    let position = self.world.get::<Position>(entity).map_or(pointer, |p| p.0);
+                    self.world
+                        .entity_mut(entity)
+                        .insert((
+                            Selected,
+                            Dragged {
+                                offset: position - pointer,
+                            },
+                        ))
+                        .remove::<Path>();
+                }
+                None => self.send_selected(pointer),
+            }
+        }
+        if self.mouse.released(picking::LEFT) {
+            let dragged: Vec<Entity> = self
+                .world
+                .query_filtered::<Entity, With<Dragged>>()
+                .iter(&self.world)
+                .collect();
+            for entity in dragged {
+                self.world.entity_mut(entity).remove::<Dragged>();
+            }
+        }
+
+        // the benchmark needs its counts left alone
+        if self.mouse.pressed(picking::RIGHT) && self.bench.is_none() {
+            if let Some(entity) = hit {
+                self.world.despawn(entity);
+            } else if self.count() < MAX_DOODS
+                && !self.world.resource::<TileMap>().is_blocked_at(pointer)
+            {
+                let mut doods = new_doods(&mut self.world, 1);
+                for dood in &mut doods {
+                    dood.position = Position(pointer);
+                }
+                self.world.spawn_batch(doods);
+            }
+        }
+    }
+
+    fn clear_selection(&mut self) {
+        let selected: Vec<Entity> = self
+            .world
+            .query_filtered::<Entity, With<Selected>>()
+            .iter(&self.world)
+            .collect();
+        for entity in selected {
+            self.world.entity_mut(entity).remove::<Selected>();
+        }
+    }
+
+    /// Gives every selected dood a path to `target` around the blocked
+    /// tiles. Doods with no way there stay where they are.
+    fn send_selected(&mut self, target: PointF) {
+        let mut selected = self
+            .world
+            .query_filtered::<(Entity, &Position), With<Selected>>();
+        if selected.iter(&self.world).next().is_none() {
+            return;
+        }
+        let map = self.world.resource::<TileMap>();
+        let Some(goal) = map.tile_at(target).filter(|tile| !map.is_blocked(*tile)) else {
+            return;
+        };
+        // one search from the goal serves the whole selection
+        let flow = map.flow_to(goal);
+        let paths: Vec<(Entity, Path)> = selected
+            .iter(&self.world)
+            .filter_map(|(entity, position)| {
+                let mut waypoints = map.path(&flow, position.0)?;
+                // the last step goes to the spot clicked, not the tile's middle
+                match waypoints.last_mut() {
+                    Some(last) => *last = target,
+                    None => waypoints.push(target),
+                }
+                Some((entity, Path { waypoints, next: 0 }))
+            })
+            .collect();
+
+        for (entity, path) in paths {
+            self.world.entity_mut(entity).insert(path);
+        }
+    }
+
+    fn draw_map(&mut self, ctx: &mut BTerm) {
+        if self.map_drawn {
+            return;
+        }
+        self.map_drawn = true;
+
+        ctx.set_active_console(MAP_CONSOLE);
+        ctx.cls();
+        let map = self.world.resource::<TileMap>();
+        // each tile is a square of text characters
+        let chars = (map.tile_size / 8.0) as i32;
+        for (tile, blocked) in map.tiles() {
+            let (fg, bg, glyph) = if blocked {
+                (RGB::named(GREY40), RGB::named(GREY20), to_cp437('▒'))
+            } else {
+                (RGB::named(BLACK), RGB::from_u8(18, 24, 18), to_cp437(' '))
+            };
+            for y in 0..chars {
+                for x in 0..chars {
+                    ctx.set(tile.x * chars + x, tile.y * chars + y, fg, bg, glyph);
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: Option<VirtualKeyCode>) {
+        let behaviour = match key {
+            Some(VirtualKeyCode::Space) => {
+                self.seek_pointer = !self.seek_pointer;
+                return;
+            }
+            Some(VirtualKeyCode::Escape) => {
+                self.clear_selection();
+                return;
+            }
+            // the benchmark picks its own counts
+            Some(VirtualKeyCode::Equals | VirtualKeyCode::Plus | VirtualKeyCode::NumpadAdd)
+                if self.bench.is_none() =>
+            {
+                let count = self.count();
+                self.set_count(count * 2);
+                return;
+            }
+            Some(VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract)
+                if self.bench.is_none() =>
+            {
+                let count = self.count();
+                self.set_count(count / 2);
+                return;
+            }
+            Some(VirtualKeyCode::Key1) => 0,
+            Some(VirtualKeyCode::Key2) => 1,
+            Some(VirtualKeyCode::Key3) => 2,
+            Some(VirtualKeyCode::Key4) => 3,
+            Some(VirtualKeyCode::Key5) => 4,
+            Some(VirtualKeyCode::Key6) => 5,
+            Some(VirtualKeyCode::Key7) => 6,
+            _ => return,
+        };
+        // number keys switch a behaviour off, or back to its usual weight
+        let mut defaults = Weights::DEFAULT;
+        let mut steering = self.world.resource_mut::<Steering>();
+        if let (Some(current), Some(default)) = (
+            steering.weights.get_mut(behaviour),
+            defaults.get_mut(behaviour),
+        ) {
+            *current = if *current > 0.0 { 0.0 } else { *default };
+        }
+    }
+
+    fn print_controls(&mut self, ctx: &mut BTerm) {
+        let pointer = if self.seek_pointer { "seek" } else { "flee" };
+        let mut weights = self.world.resource::<Steering>().weights;
+        ctx.print(1, 4, format!("Space: {} the pointer", pointer));
+        for (index, name) in Weights::NAMES.iter().enumerate() {
+            let value = weights.get_mut(index).map_or(0.0, |w| *w);
+            let color = if value > 0.0 { WHITE } else { GREY };
+            ctx.print_color(
+                1,
+                6 + index as i32,
+                color,
+                BLACK,
+                format!("{} {:<12}{:.1}", index + 1, name, value),
+            );
+        }
+    }
+
+    fn print_timings(&mut self, ctx: &mut BTerm) {
+        let t = self.timings;
+        let count = self.count();
+        ctx.print(1, 14, format!("{} doods (+/- to change)", count));
+        ctx.print(1, 15, format!("frame  {:>7.2} ms", t.frame_ms));
+        ctx.print(1, 16, format!("update {:>7.2} ms", t.update_ms));
+        ctx.print(1, 17, format!("submit {:>7.2} ms", t.submit_ms));
+        if let Some(bench) = &self.bench {
+            ctx.print_color(1, 19, YELLOW, BLACK, bench.progress());
+        }
+    }
+
+    /// Details of the first selected dood at the bottom of the screen,
+    /// and a box around every selected one.
+    fn print_selection(&mut self, ctx: &mut BTerm) {
+        ctx.print(1, 42, "Click: select and drag   Ctrl+click: select more");
+        ctx.print(
+            1,
+            43,
+            "Click ground: walk there   Esc: deselect   Right click: add or remove",
+        );
+        let mut selected = self.world.query_filtered::<(
+            Entity,
+            &Position,
+            &Velocity,
+            &Animation,
+            &Sprite,
+            Option<&Path>,
+        ), With<Selected>>();
+        let library = self.world.resource::<Library>();
+        let sprites = self.world.resource::<Sprites>();
+        let map = self.world.resource::<TileMap>();
+
+        let mut count = 0;
+        for (entity, position, velocity, animation, sprite, path) in selected.iter(&self.world) {
+            count += 1;
+            let mut frame = *sprites.index.frame(sprite.index);
+            if sprite.flip {
+                frame = frame.mirrored();
+            }
+            // the text console has one character to every 8x8 pixels
+            let rect = frame.place(position.0, sprites.scale);
+            let left = rect.x1.div_euclid(8);
+            let top = rect.y1.div_euclid(8);
+            let right = (rect.x2 + 7).div_euclid(8);
+            let bottom = (rect.y2 + 7).div_euclid(8);
+            ctx.draw_hollow_box(left, top, right - left, bottom - top, YELLOW, BLACK);
+            if count > 1 {
+                continue;
+            }
+
+            let clip = library.clips.get(animation.animator.clip);
+            ctx.print_color(1, 45, YELLOW, BLACK, format!("Dood {}", entity));
+            ctx.print(
+                1,
+                46,
+                format!(
+                    "at ({:.0}, {:.0}) going {:.0} px/s",
+                    position.0.x,
+                    position.0.y,
+                    velocity.0.mag()
+                ),
+            );
+            ctx.print(
+                1,
+                47,
+                format!(
+                    "clip '{}' at {:.0} ms, frame {}",
+                    clip.name, animation.animator.elapsed_ms, sprite.index
+                ),
+            );
+            if let Some(goal) = path.and_then(|path| path.waypoints.last()) {
+                let to_go = path.map_or(0, |path| path.waypoints.len() - path.next);
+                let tile = map.tile_at(*goal).unwrap_or_else(Point::zero);
+                ctx.print(
+                    1,
+                    48,
+                    format!("walking to ({}, {}), {} tiles to go", tile.x, tile.y, to_go),
+                );
+            }
+        }
+        if count > 1 {
+            ctx.print_color(30, 45, YELLOW, BLACK, format!("and {} more", count - 1));
+        }
+    }
+
+    /// Feeds a frame to the running benchmark, if any, and saves its
+    /// results once it has gone through every count.
+    fn run_bench(&mut self, ctx: &mut BTerm, timings: Timings) {
+        let Some(bench) = &mut self.bench else {
+            return;
+        };
+        bench.record(timings);
+        if !bench.is_finished() {
+            let doods = bench.doods();
+            if doods != self.count() {
+                self.set_count(doods);
+            }
+            return;
+        }
+
+        print!("{}", bench.csv());
+        match bench.save() {
+            Ok(()) => println!("added to {}", bench::RESULTS),
+            Err(err) => eprintln!("{}: {}", bench::RESULTS, err),
+        }
+        self.bench = None;
+        ctx.quit();
+    }
 }
 
 impl GameState for State {
     fn tick(&mut self, ctx: &mut BTerm) {
-        ctx.set_active_console(1);
+        self.draw_map(ctx);
+
+        ctx.set_active_console(HUD_CONSOLE);
         ctx.cls();
         ctx.print(1, 1, "Watch them go!");
         ctx.printer(
             1,
             2,
-            &format!("#[pink]FPS: #[]{}", ctx.fps),
+            format!("#[pink]FPS: #[]{}", ctx.fps),
             TextAlign::Left,
             None,
         );
 
-        ctx.set_active_console(0);
+        self.handle_key(ctx.key);
+        self.print_controls(ctx);
+        self.print_timings(ctx);
+
+        ctx.set_active_console(SPRITE_CONSOLE);
         ctx.cls();
 
-        for dood in self.doods.iter() {
-            ctx.add_sprite(
-                Rect::with_size(dood.x, dood.y, 32, 32),
-                400 - dood.y,
-                RGBA::from_f32(1.0, 1.0, 1.0, 1.0),
-                self.frame % 4,
-            )
-        }
+        let pointer = ctx.mouse_point();
+        let pointer = PointF::new(pointer.x as f32, pointer.y as f32);
+        self.handle_mouse(pointer);
 
-        self.timer += ctx.frame_time_ms;
-        if self.timer > 66.0 {
-            self.timer = 0.0;
-            self.frame += 1;
+        let started = now_ms();
+        let goal = if self.seek_pointer {
+            Goal::Seek(pointer)
+        } else {
+            Goal::Flee(pointer)
+        };
+        self.world.insert_resource(Target(Some(goal)));
+        self.world.insert_resource(FrameTime {
+            ms: ctx.frame_time_ms,
+        });
+        self.update.run(&mut self.world);
 
-            for dood in self.doods.iter_mut() {
-                dood.x += self.rng.range(0, 3) - 1;
-                dood.y += self.rng.range(0, 3) - 1;
+        let updated = now_ms();
+        self.render.run(&mut self.world);
+        for draw in &self.world.resource::<DrawList>().0 {
+            let mut rect = draw.rect;
+            // the texture's left edge goes to x1, so swapping mirrors it
+            if draw.flip {
+                std::mem::swap(&mut rect.x1, &mut rect.x2);
             }
+            ctx.add_sprite(rect, draw.z_order, draw.tint, draw.sprite);
         }
+        let submitted = now_ms();
+
+        ctx.set_active_console(HUD_CONSOLE);
+        self.print_selection(ctx);
+        ctx.set_active_console(SPRITE_CONSOLE);
+
+        let timings = Timings {
+            frame_ms: ctx.frame_time_ms,
+            update_ms: (updated - started) as f32,
+            submit_ms: (submitted - updated) as f32,
+        };
+        self.timings.smooth(timings);
+        self.run_bench(ctx, timings);
     }
 }
 
-embedded_resource!(NYAN_CAT, "../resources/sprite_dood.png");
+/// Up to `count` doods at random open places, each walking its own way.
+/// There are none if the map has no open tile.
+fn new_doods(world: &mut World, count: usize) -> Vec<DoodBundle> {
+    world.resource_scope(|world, mut rng: Mut<Rng>| {
+        let library = world.resource::<Library>();
+        // any clip but the stumble, started at a random point and pace
+        let clips: Vec<usize> = (0..library.clips.len())
+            .filter(|clip| *clip != library.stumble)
+            .collect();
+        let map = world.resource::<TileMap>();
+        let open: Vec<Point> = map
+            .tiles()
+            .filter(|(_, blocked)| !blocked)
+            .map(|(tile, _)| tile)
+            .collect();
+        let rng = &mut rng.0;
+
+        (0..count)
+            .filter_map(|_| {
+                // anywhere on an open tile; `main` makes sure there is one
+                let tile = *rng.random_slice_entry(&open)?;
+                let clip = rng
+                    .random_slice_entry(&clips)
+                    .copied()
+                    .unwrap_or(library.walk);
+                let angle = rng.range(0.0, std::f32::consts::TAU);
+                let animator = Animator::new(clip, rng.range(0.0, 1000.0));
+                Some(DoodBundle {
+                    dood: Dood,
+                    position: Position(PointF::new(
+                        (tile.x as f32 + rng.range(0.0, 1.0)) * map.tile_size,
+                        (tile.y as f32 + rng.range(0.0, 1.0)) * map.tile_size,
+                    )),
+                    velocity: Velocity(PointF::new(angle.cos(), angle.sin()) * 20.0),
+                    wander: Wander { angle: 0.0 },
+                    sprite: Sprite {
+                        index: 0,
+                        tint: RGBA::from_f32(1.0, 1.0, 1.0, 1.0),
+                        rank: 0,
+                        flip: false,
+                    },
+                    animation: Animation {
+                        animator,
+                        pace: rng.range(0.8, 1.25),
+                    },
+                })
+            })
+            .collect()
+    })
+}
+
+struct Options {
+    doods: usize,
+    scale: f32,
+    /// Directory of images replacing the embedded ones by name.
+    assets: Option<PathBuf>,
+    /// Run the benchmark, labelling its results with this.
+    bench: Option<String>,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        doods: 100,
+        scale: 1.0,
+        assets: None,
+        bench: None,
+    };
+    let mut label = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--doods" => options.doods = value()?.parse().map_err(|_| "bad --doods")?,
+            "--scale" => options.scale = value()?.parse().map_err(|_| "bad --scale")?,
+            "--assets" => options.assets = Some(PathBuf::from(value()?)),
+            "--bench" => options.bench = Some(String::new()),
+            "--label" => label = Some(value()?),
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+    if options.scale.is_nan() || options.scale <= 0.0 {
+        return Err("--scale must be more than 0".to_string());
+    }
+    if let Some(bench) = &mut options.bench {
+        *bench = label.unwrap_or_else(|| "unlabelled".to_string());
+    }
+
+    Ok(options)
+}
+
+embedded_resource!(SPRITE_DOOD, "../resources/sprite_dood.png");
+embedded_resource!(TERMINAL_FONT, "../resources/terminal8x8.png");
+
+const DOOD: Asset = Asset {
+    name: "sprite_dood.png",
+    bytes: SPRITE_DOOD,
+};
+const FONT: Asset = Asset {
+    name: "terminal8x8.png",
+    bytes: TERMINAL_FONT,
+};
 
 fn main() -> BError {
-    link_resource!(NYAN_CAT, "resources/sprite_dood.png");
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!(
+                "usage: sprites_example [--doods N] [--scale F] [--assets DIR] [--bench [--label TEXT]]"
+            );
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(err) = assets::link(&[DOOD, FONT], options.assets.as_deref()) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+
+    let sheet = aseprite::import(SHEET, &DOOD.path())
+        .map_err(|err| format!("sprite_dood.json: {}", err))?;
 
     let context = BTermBuilder::new()
-        .with_sprite_console(640, 400, 0)
-        .with_font("terminal8x8.png", 8, 8)
-        .with_simple_console_no_bg(80, 50, "terminal8x8.png")
+        .with_font(FONT.name, 8, 8)
+        .with_simple_console(WIDTH / 8, HEIGHT / 8, FONT.name)
+        .with_sprite_console(WIDTH as u32, HEIGHT as u32, 0)
+        .with_simple_console_no_bg(80, 50, FONT.name)
         .with_title("Bracket Terminal - Sprite Console")
-        .with_sprite_sheet(
-            SpriteSheet::new("resources/sprite_dood.png")
-                .add_sprite(Rect::with_size(0, 0, 85, 132))
-                .add_sprite(Rect::with_size(85, 0, 85, 132))
-                .add_sprite(Rect::with_size(170, 0, 85, 132))
-                .add_sprite(Rect::with_size(255, 0, 85, 132)),
-        )
+        .with_sprite_sheet(sheet.sheet)
         .with_vsync(false)
         .build()?;
 
-    let mut rng = RandomNumberGenerator::new();
-    let mut doods = Vec::new();
-    for _ in 0..100 {
-        doods.push(Dood {
-            x: rng.range(0, 640),
-            y: rng.range(0, 400),
-        });
+    let mut clips = sheet.clips;
+    clips.extend(Clips::parse(CLIPS).map_err(|err| format!("doods.anim: {}", err))?);
+    clips.check_sprites(sheet.sprites.len())?;
+    let walk = clips
+        .find("walk")
+        .ok_or("sprite_dood.json: no 'walk' tag")?;
+    let stumble = clips
+        .find("stumble")
+        .ok_or("doods.anim: no 'stumble' clip")?;
+
+    // every dood frame is the same size, so one stands for them all
+    let frame = *sheet.sprites.frame(0);
+    let scale = (frame.fit(f32::MAX, DOOD_HEIGHT) * options.scale)
+        .min(frame.fit(WIDTH as f32, HEIGHT as f32));
+
+    // keep whole doods on screen
+    let extent = frame.place(PointF::zero(), scale);
+    let steering = Steering::new(Rect::with_exact(
+        -extent.x1,
+        -extent.y1,
+        WIDTH - extent.x2,
+        HEIGHT - extent.y2,
+    ));
+
+    let map = TileMap::parse(MAP, TILE_SIZE).map_err(|err| format!("obstacles.map: {}", err))?;
+    if map.tiles().all(|(_, blocked)| blocked) {
+        return Err("obstacles.map: no open tile for the doods to stand on".into());
     }
 
-    let gs = State {
-        frame: 0,
-        timer: 0.0,
-        doods,
-        rng,
+    let mut world = World::new();
+    world.insert_resource(steering);
+    world.insert_resource(map);
+    world.insert_resource(Rng(RandomNumberGenerator::new()));
+    world.insert_resource(FrameTime::default());
+    world.insert_resource(Target::default());
+    world.insert_resource(Cursor(PointF::zero()));
+    world.insert_resource(Library {
+        clips,
+        walk,
+        stumble,
+    });
+    world.insert_resource(Sprites {
+        index: sheet.sprites,
+        scale,
+    });
+    world.insert_resource(DrawList::default());
+
+    let mut gs = State {
+        world,
+        update: systems::update(),
+        render: systems::render(),
+        seek_pointer: false,
+        mouse: MouseButtons::default(),
+        map_drawn: false,
+        timings: Timings::default(),
+        bench: options.bench.as_deref().map(Bench::new),
     };
+    let doods = gs.bench.as_ref().map_or(options.doods, Bench::doods);
+    gs.set_count(doods);
 
     main_loop(context, gs)
-}
\ No newline at end of file
+}
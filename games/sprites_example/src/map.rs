@@ -0,0 +1,266 @@
+//! The ground the doods walk on: a grid of tiles that are either open or
+//! blocked, loaded from a text file, and the paths across it.
+
+use bevy_ecs::prelude::*;
+use bracket_lib::prelude::*;
+
+/// Further than any path on the map is long, in tiles.
+const MAX_PATH: f32 = 4000.0;
+/// Cost of a diagonal step relative to a straight one.
+const DIAGONAL: f32 = std::f32::consts::SQRT_2;
+
+#[derive(Resource)]
+pub struct TileMap {
+    pub width: i32,
+    pub height: i32,
+    /// Edge length of a tile in sprite console pixels.
+    pub tile_size: f32,
+    blocked: Vec<bool>,
+}
+
+impl TileMap {
+    /// Reads a map drawn with `#` for blocked tiles and `.` for open
+    /// ones, a row per line. Lines starting with `# ` are comments.
+    pub fn parse(text: &str, tile_size: f32) -> Result<Self, String> {
+        let mut rows: Vec<Vec<bool>> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with("# ") {
+                continue;
+            }
+            let row = line
+                .chars()
+                .map(|c| match c {
+                    '#' => Ok(true),
+                    '.' => Ok(false),
+                    other => Err(format!("line {}: unknown tile '{}'", number + 1, other)),
+                })
+                .collect::<Result<Vec<bool>, String>>()?;
+            if let Some(first) = rows.first() {
+                if row.len() != first.len() {
+                    return Err(format!(
+                        "line {}: {} tiles wide, not {}",
+                        number + 1,
+                        row.len(),
+                        first.len()
+                    ));
+                }
+            }
+            rows.push(row);
+        }
+        if rows.is_empty() {
+            return Err("no tiles".to_string());
+        }
+
+        Ok(Self {
+            width: rows[0].len() as i32,
+            height: rows.len() as i32,
+            tile_size,
+            blocked: rows.concat(),
+        })
+    }
+
+    /// The tile under a point, if it is on the map.
+    pub fn tile_at(&self, point: PointF) -> Option<Point> {
+        let tile = Point::new(
+            (point.x / self.tile_size).floor() as i32,
+            (point.y / self.tile_size).floor() as i32,
+        );
+        self.in_bounds(tile).then_some(tile)
+    }
+
+    /// The middle of a tile in pixels.
+    pub fn centre(&self, tile: Point) -> PointF {
+        PointF::new(
+            (tile.x as f32 + 0.5) * self.tile_size,
+            (tile.y as f32 + 0.5) * self.tile_size,
+        )
+    }
+
+    pub fn is_blocked(&self, tile: Point) -> bool {
+        !self.in_bounds(tile) || self.blocked[self.point2d_to_index(tile)]
+    }
+
+    /// Whether a point is on a blocked tile. Points off the map are
+    /// left to the steering bounds.
+    pub fn is_blocked_at(&self, point: PointF) -> bool {
+        self.tile_at(point)
+            .is_some_and(|tile| self.blocked[self.point2d_to_index(tile)])
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = (Point, bool)> + '_ {
+        self.blocked
+            .iter()
+            .enumerate()
+            .map(|(index, blocked)| (self.index_to_point2d(index), *blocked))
+    }
+
+    /// Distances to `goal` from every tile, for any number of walkers to
+    /// find their way there from one search.
+    pub fn flow_to(&self, goal: Point) -> DijkstraMap {
+        let goal = self.point2d_to_index(goal);
+        let mut flow = DijkstraMap::new(self.width, self.height, &[goal], self, MAX_PATH);
+        // bracket only scores the tiles around a start, never the start
+        // itself, which would leave it uphill of its neighbours
+        flow.map[goal] = 0.0;
+        flow
+    }
+
+    /// Tile centres from the one after `from` to the goal of `flow`,
+    /// following it downhill, or `None` if the goal can't be reached.
+    pub fn path(&self, flow: &DijkstraMap, from: PointF) -> Option<Vec<PointF>> {
+        let mut index = self.point2d_to_index(self.tile_at(from)?);
+        if flow.map[index] == f32::MAX {
+            return None;
+        }
+        let mut waypoints = Vec::new();
+        while flow.map[index] > 0.0 {
+            let next = DijkstraMap::find_lowest_exit(flow, index, self)?;
+            if flow.map[next] >= flow.map[index] {
+                return None;
+            }
+            index = next;
+            waypoints.push(self.centre(self.index_to_point2d(index)));
+        }
+        Some(waypoints)
+    }
+}
+
+impl Algorithm2D for TileMap {
+    fn dimensions(&self) -> Point {
+        Point::new(self.width, self.height)
+    }
+}
+
+impl BaseMap for TileMap {
+    fn is_opaque(&self, index: usize) -> bool {
+        self.blocked[index]
+    }
+
+    fn get_available_exits(&self, index: usize) -> SmallVec<[(usize, f32); 10]> {
+        let tile = self.index_to_point2d(index);
+        let mut exits = SmallVec::new();
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let next = tile + Point::new(dx, dy);
+                if (dx, dy) == (0, 0) || self.is_blocked(next) {
+                    continue;
+                }
+                if dx != 0 && dy != 0 {
+                    // no squeezing diagonally between two blocked tiles
+                    let side_x = tile + Point::new(dx, 0);
+                    let side_y = tile + Point::new(0, dy);
+                    if self.is_blocked(side_x) || self.is_blocked(side_y) {
+                        continue;
+                    }
+                    exits.push((self.point2d_to_index(next), DIAGONAL));
+                } else {
+                    exits.push((self.point2d_to_index(next), 1.0));
+                }
+            }
+        }
+        exits
+    }
+
+    fn get_pathing_distance(&self, from: usize, to: usize) -> f32 {
+        DistanceAlg::Pythagoras.distance2d(self.index_to_point2d(from), self.index_to_point2d(to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(text: &str) -> TileMap {
+        TileMap::parse(text, 8.0).unwrap()
+    }
+
+    /// The tiles a path from `from` to `goal` walks through, checking
+    /// each step is to an open neighbour without cutting a corner.
+    fn walk(map: &TileMap, from: Point, goal: Point) -> Option<Vec<Point>> {
+        let flow = map.flow_to(goal);
+        let waypoints = map.path(&flow, map.centre(from))?;
+        let tiles: Vec<Point> = waypoints
+            .iter()
+            .map(|point| map.tile_at(*point).unwrap())
+            .collect();
+
+        let mut at = from;
+        for tile in &tiles {
+            let step = *tile - at;
+            assert!(
+                step.x.abs() <= 1 && step.y.abs() <= 1,
+                "{:?} to {:?}",
+                at,
+                tile
+            );
+            assert!(!map.is_blocked(*tile), "{:?} is blocked", tile);
+            let side_x = at + Point::new(step.x, 0);
+            let side_y = at + Point::new(0, step.y);
+            assert!(
+                !map.is_blocked(side_x) && !map.is_blocked(side_y),
+                "cut the corner from {:?} to {:?}",
+                at,
+                tile
+            );
+            at = *tile;
+        }
+        assert_eq!(at, goal);
+        Some(tiles)
+    }
+
+    #[test]
+    fn parses_tiles_and_skips_comments() {
+        let map = map("# a comment\n..#\n\n#..\n");
+        assert_eq!((map.width, map.height), (3, 2));
+        let blocked: Vec<Point> = map
+            .tiles()
+            .filter(|(_, blocked)| *blocked)
+            .map(|(tile, _)| tile)
+            .collect();
+        assert_eq!(blocked, vec![Point::new(2, 0), Point::new(0, 1)]);
+        assert!(map.is_blocked(Point::new(-1, 0)));
+        assert!(map.is_blocked(Point::new(3, 1)));
+    }
+
+    #[test]
+    fn rejects_bad_maps() {
+        assert!(TileMap::parse("", 8.0).is_err());
+        assert!(TileMap::parse("# only a comment\n", 8.0).is_err());
+        let err = TileMap::parse("...\n.x.\n", 8.0).err().unwrap();
+        assert!(err.contains("line 2"), "{}", err);
+        let err = TileMap::parse("...\n....\n", 8.0).err().unwrap();
+        assert!(err.contains("4 tiles wide"), "{}", err);
+    }
+
+    #[test]
+    fn routes_around_walls() {
+        let map = map(".....\n####.\n.....\n.####\n.....\n");
+        let tiles = walk(&map, Point::new(0, 0), Point::new(4, 4)).unwrap();
+        assert!(tiles.contains(&Point::new(4, 1)));
+        assert!(tiles.contains(&Point::new(0, 3)));
+    }
+
+    #[test]
+    fn does_not_cut_corners() {
+        let ring = map("...\n.#.\n...\n");
+        let tiles = walk(&ring, Point::new(0, 0), Point::new(2, 2)).unwrap();
+        assert_eq!(tiles.len(), 4);
+
+        // the only way through is diagonally between two blocked tiles
+        let pinched = map(".#\n#.\n");
+        assert!(walk(&pinched, Point::new(0, 0), Point::new(1, 1)).is_none());
+    }
+
+    #[test]
+    fn finds_no_path_to_a_walled_off_goal() {
+        let map = map("..#..\n..#..\n..#..\n");
+        assert!(walk(&map, Point::new(0, 0), Point::new(4, 2)).is_none());
+    }
+
+    #[test]
+    fn arriving_at_the_goal_needs_no_waypoints() {
+        let map = map("...\n");
+        assert_eq!(walk(&map, Point::new(1, 0), Point::new(1, 0)), Some(vec![]));
+    }
+}
@@ -0,0 +1,62 @@
+//! Finding which sprite is under the mouse, and telling clicks from
+//! holds.
+//!
+//! Hit tests run against the last frame's [`DrawList`], so they match
+//! what is on screen rather than where things have moved to since.
+
+use bevy_ecs::prelude::*;
+use bracket_lib::prelude::*;
+
+use crate::resources::DrawList;
+
+pub const LEFT: usize = 0;
+pub const RIGHT: usize = 1;
+
+/// The entity whose sprite is on top at `point`. Sprites are tested by
+/// their whole rectangle, transparent corners included.
+pub fn pick(list: &DrawList, point: PointF) -> Option<Entity> {
+    let point = Point::new(point.x as i32, point.y as i32);
+    // of equal z-orders the one added last is drawn last, so on top
+    list.0
+        .iter()
+        .filter(|draw| draw.rect.point_in_rect(point))
+        .max_by_key(|draw| draw.z_order)
+        .map(|draw| draw.entity)
+}
+
+/// Mouse buttons as of this frame and the one before, to tell presses
+/// and releases from buttons held down.
+#[derive(Default)]
+pub struct MouseButtons {
+    down: [bool; 2],
+    was_down: [bool; 2],
+    /// Whether Ctrl is held, for clicks that add to what is selected.
+    pub ctrl: bool,
+}
+
+impl MouseButtons {
+    /// Reads the buttons for a new frame. Browsers report every button
+    /// as the left one, so holding Shift makes it the right one there.
+    pub fn update(&mut self) {
+        let input = INPUT.lock();
+        let held = |keys: [VirtualKeyCode; 2]| {
+            keys.iter().any(|key| input.key_pressed_set().contains(key))
+        };
+        let shift = held([VirtualKeyCode::LShift, VirtualKeyCode::RShift]);
+        self.ctrl = held([VirtualKeyCode::LControl, VirtualKeyCode::RControl]);
+        let left = input.is_mouse_button_pressed(LEFT);
+        self.was_down = self.down;
+        self.down = [
+            left && !shift,
+            input.is_mouse_button_pressed(RIGHT) || (left && shift),
+        ];
+    }
+
+    pub fn pressed(&self, button: usize) -> bool {
+        self.down[button] && !self.was_down[button]
+    }
+
+    pub fn released(&self, button: usize) -> bool {
+        !self.down[button] && self.was_down[button]
+    }
+}
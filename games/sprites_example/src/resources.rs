@@ -0,0 +1,66 @@
+//! World-wide state the systems share, as opposed to per-entity
+//! components. The steering settings are a resource too, in
+//! [`steering`](crate::steering).
+
+use bevy_ecs::prelude::*;
+use bracket_lib::prelude::*;
+use snake_common::clips::Clips;
+use snake_common::sheet::SpriteIndex;
+
+use crate::steering::Goal;
+
+/// How long the frame being simulated took.
+#[derive(Resource, Clone, Copy, Default, Debug)]
+pub struct FrameTime {
+    pub ms: f32,
+}
+
+impl FrameTime {
+    pub fn seconds(&self) -> f32 {
+        self.ms / 1000.0
+    }
+}
+
+/// Where the mouse pointer is, in sprite console pixels.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Cursor(pub PointF);
+
+/// What every dood reacts to this frame, such as the mouse pointer.
+#[derive(Resource, Clone, Copy, Default, Debug)]
+pub struct Target(pub Option<Goal>);
+
+#[derive(Resource)]
+pub struct Rng(pub RandomNumberGenerator);
+
+/// The dood animations and the clips systems ask for by name.
+#[derive(Resource)]
+pub struct Library {
+    pub clips: Clips,
+    pub walk: usize,
+    pub stumble: usize,
+}
+
+/// How sprites map onto the screen.
+#[derive(Resource)]
+pub struct Sprites {
+    pub index: SpriteIndex,
+    /// Size on screen relative to the frames on the texture.
+    pub scale: f32,
+}
+
+/// One sprite ready to hand to the console.
+#[derive(Clone, Copy, Debug)]
+pub struct Draw {
+    pub entity: Entity,
+    pub rect: Rect,
+    pub z_order: i32,
+    pub tint: RGBA,
+    pub sprite: usize,
+    pub flip: bool,
+}
+
+/// Sprites the render system has laid out, for the game loop to submit
+/// to the console once the schedule has run. Systems can't reach the
+/// `BTerm` themselves.
+#[derive(Resource, Default)]
+pub struct DrawList(pub Vec<Draw>);
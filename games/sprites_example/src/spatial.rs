@@ -0,0 +1,123 @@
+//! A uniform grid of buckets for finding nearby points without checking
+//! every other point, rebuilt from scratch each frame.
+
+use std::collections::HashMap;
+
+use bracket_lib::prelude::PointF;
+
+pub struct SpatialHash {
+    cell_size: f32,
+    buckets: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    /// Queries are cheapest with cells about as big as the usual query
+    /// radius.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            buckets: HashMap::new(),
+        }
+    }
+
+    fn key(&self, point: PointF) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Replaces the contents with `points`, found again by their index.
+    /// Emptied buckets are kept so their memory is reused next frame.
+    pub fn rebuild(&mut self, points: impl Iterator<Item = PointF>) {
+        for bucket in self.buckets.values_mut() {
+            bucket.clear();
+        }
+        for (index, point) in points.enumerate() {
+            let key = self.key(point);
+            self.buckets.entry(key).or_default().push(index);
+        }
+    }
+
+    /// Calls `visit` with the index of every point that may be within
+    /// `radius` of `center`, until it returns false. Callers still check
+    /// the exact distance.
+    pub fn for_each_near(&self, center: PointF, radius: f32, mut visit: impl FnMut(usize) -> bool) {
+        let reach = PointF::new(radius, radius);
+        let (x1, y1) = self.key(center - reach);
+        let (x2, y2) = self.key(center + reach);
+        for y in y1..=y2 {
+            for x in x1..=x2 {
+                let bucket = self.buckets.get(&(x, y)).map_or(&[][..], |b| &b[..]);
+                for index in bucket {
+                    if !visit(*index) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bracket_lib::prelude::RandomNumberGenerator;
+
+    use super::*;
+
+    #[test]
+    fn finds_every_point_within_the_radius() {
+        let mut rng = RandomNumberGenerator::seeded(7);
+        // straddling the origin, so some cells have negative keys
+        let points: Vec<PointF> = (0..400)
+            .map(|_| PointF::new(rng.range(-100.0, 100.0), rng.range(-100.0, 100.0)))
+            .collect();
+        let mut hash = SpatialHash::new(16.0);
+        hash.rebuild(points.iter().copied());
+
+        for center in [
+            PointF::new(0.0, 0.0),
+            PointF::new(-15.9, 16.1),
+            PointF::new(-64.0, -64.0),
+            PointF::new(47.5, -0.5),
+        ] {
+            for radius in [5.0, 16.0, 37.0] {
+                let mut found = Vec::new();
+                hash.for_each_near(center, radius, |index| {
+                    found.push(index);
+                    true
+                });
+                for (index, point) in points.iter().enumerate() {
+                    if (*point - center).mag() <= radius {
+                        assert!(found.contains(&index), "{:?} near {:?}", point, center);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stops_when_told_to() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.rebuild((0..10).map(|i| PointF::new(i as f32, -(i as f32))));
+        let mut visits = 0;
+        hash.for_each_near(PointF::zero(), 20.0, |_| {
+            visits += 1;
+            visits < 3
+        });
+        assert_eq!(visits, 3);
+    }
+
+    #[test]
+    fn rebuilding_forgets_old_points() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.rebuild([PointF::new(1.0, 1.0)].into_iter());
+        hash.rebuild([PointF::new(-50.0, -50.0)].into_iter());
+        let mut found = Vec::new();
+        hash.for_each_near(PointF::new(1.0, 1.0), 5.0, |index| {
+            found.push(index);
+            true
+        });
+        assert!(found.is_empty());
+    }
+}
@@ -0,0 +1,359 @@
+//! Steering behaviours after Craig Reynolds' boids. Each behaviour looks
+//! at an agent's surroundings and asks for a change of velocity; the
+//! weighted sum of those, capped at `max_force`, is what the agent gets.
+//!
+//! Positions are in sprite console pixels and times in seconds.
+
+use bevy_ecs::prelude::*;
+use bracket_lib::prelude::*;
+
+use crate::spatial::SpatialHash;
+
+/// What steering needs to know about one agent, gathered from its
+/// components and written back once it has been steered.
+#[derive(Clone, Copy, Debug)]
+pub struct Agent {
+    pub position: PointF,
+    pub velocity: PointF,
+    /// Where on its wander circle the agent is heading, in radians.
+    pub wander_angle: f32,
+}
+
+/// Something every agent reacts to, such as the mouse pointer.
+#[derive(Clone, Copy, Debug)]
+pub enum Goal {
+    Seek(PointF),
+    Flee(PointF),
+}
+
+/// How much each behaviour counts. 0 turns one off.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Weights {
+    pub wander: f32,
+    pub seek: f32,
+    pub flee: f32,
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+    pub containment: f32,
+}
+
+impl Weights {
+    pub const DEFAULT: Weights = Weights {
+        wander: 1.0,
+        seek: 1.0,
+        flee: 2.0,
+        separation: 1.5,
+        alignment: 0.6,
+        cohesion: 0.4,
+        containment: 3.0,
+    };
+
+    pub const NAMES: [&'static str; 7] = [
+        "wander",
+        "seek",
+        "flee",
+        "separation",
+        "alignment",
+        "cohesion",
+        "containment",
+    ];
+
+    /// The weight of a behaviour by its place in [`Weights::NAMES`].
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut f32> {
+        match index {
+            0 => Some(&mut self.wander),
+            1 => Some(&mut self.seek),
+            2 => Some(&mut self.flee),
+            3 => Some(&mut self.separation),
+            4 => Some(&mut self.alignment),
+            5 => Some(&mut self.cohesion),
+            6 => Some(&mut self.containment),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Steering {
+    pub weights: Weights,
+    pub max_speed: f32,
+    pub max_force: f32,
+    /// Agents closer than this count as flockmates.
+    pub neighbour_radius: f32,
+    /// Agents closer than this get pushed apart.
+    pub separation_radius: f32,
+    /// Flockmates past this many are ignored, which keeps dense crowds
+    /// from costing more per agent than sparse ones.
+    pub max_neighbours: usize,
+    /// A fleeing agent only cares about threats this close.
+    pub panic_radius: f32,
+    /// Area the agents are kept in, and how far from its edges they
+    /// start turning back.
+    pub bounds: Rect,
+    pub margin: f32,
+    /// Agents bucketed by position, so flocking only looks at nearby ones.
+    grid: SpatialHash,
+}
+
+impl Steering {
+    pub fn new(bounds: Rect) -> Self {
+        let neighbour_radius = 50.0;
+        Self {
+            weights: Weights::DEFAULT,
+            max_speed: 60.0,
+            max_force: 120.0,
+            neighbour_radius,
+            max_neighbours: 12,
+            separation_radius: 24.0,
+            panic_radius: 90.0,
+            bounds,
+            margin: 40.0,
+            grid: SpatialHash::new(neighbour_radius),
+        }
+    }
+
+    /// Changes every agent's velocity by what its behaviours ask for
+    /// over `seconds`. Moving them is left to the caller.
+    pub fn accelerate(
+        &mut self,
+        agents: &mut [Agent],
+        goal: Option<Goal>,
+        rng: &mut RandomNumberGenerator,
+        seconds: f32,
+    ) {
+        self.grid.rebuild(agents.iter().map(|agent| agent.position));
+        let forces: Vec<PointF> = (0..agents.len())
+            .map(|index| self.force(agents, index, goal, rng))
+            .collect();
+
+        for (agent, force) in agents.iter_mut().zip(forces) {
+            agent.velocity = limit(agent.velocity + force * seconds, self.max_speed);
+        }
+    }
+
+    /// `position` pulled back inside the bounds.
+    pub fn contain(&self, position: PointF) -> PointF {
+        PointF::new(
+            position
+                .x
+                .clamp(self.bounds.x1 as f32, self.bounds.x2 as f32),
+            position
+                .y
+                .clamp(self.bounds.y1 as f32, self.bounds.y2 as f32),
+        )
+    }
+
+    fn force(
+        &self,
+        agents: &mut [Agent],
+        index: usize,
+        goal: Option<Goal>,
+        rng: &mut RandomNumberGenerator,
+    ) -> PointF {
+        let w = self.weights;
+        let mut force = PointF::zero();
+
+        if w.wander > 0.0 {
+            force += self.wander(&mut agents[index], rng) * w.wander;
+        }
+        let agent = &agents[index];
+        match goal {
+            Some(Goal::Seek(target)) if w.seek > 0.0 => force += self.seek(agent, target) * w.seek,
+            Some(Goal::Flee(threat)) if w.flee > 0.0 => force += self.flee(agent, threat) * w.flee,
+            _ => {}
+        }
+        if w.separation > 0.0 || w.alignment > 0.0 || w.cohesion > 0.0 {
+            force += self.flock(agents, index);
+        }
+        if w.containment > 0.0 {
+            force += self.containment(agent) * w.containment;
+        }
+
+        limit(force, self.max_force)
+    }
+
+    /// Steering that turns the current velocity into `desired`.
+    fn steer(&self, agent: &Agent, desired: PointF) -> PointF {
+        limit(desired - agent.velocity, self.max_force)
+    }
+
+    pub fn seek(&self, agent: &Agent, target: PointF) -> PointF {
+        let offset = target - agent.position;
+        if offset.mag_sq() < 1.0 {
+            return PointF::zero();
+        }
+        self.steer(agent, offset.normalized() * self.max_speed)
+    }
+
+    pub fn flee(&self, agent: &Agent, threat: PointF) -> PointF {
+        let offset = agent.position - threat;
+        let distance = offset.mag();
+        if distance >= self.panic_radius || distance < f32::EPSILON {
+            return PointF::zero();
+        }
+        // the closer the threat, the harder it runs
+        let urgency = 1.0 - distance / self.panic_radius;
+        self.steer(agent, offset.normalized() * self.max_speed) * urgency
+    }
+
+    /// Heads for a point on a circle just ahead of the agent, nudging
+    /// that point a little each time, for smooth aimless motion.
+    pub fn wander(&self, agent: &mut Agent, rng: &mut RandomNumberGenerator) -> PointF {
+        const DISTANCE: f32 = 30.0;
+        const RADIUS: f32 = 15.0;
+        const JITTER: f32 = 0.5;
+
+        agent.wander_angle += rng.range(-JITTER, JITTER);
+        let heading = if agent.velocity.mag_sq() > f32::EPSILON {
+            agent.velocity.normalized()
+        } else {
+            PointF::new(1.0, 0.0)
+        };
+        let circle = agent.position + heading * DISTANCE;
+        let target =
+            circle + PointF::new(agent.wander_angle.cos(), agent.wander_angle.sin()) * RADIUS;
+        self.seek(agent, target)
+    }
+
+    /// Separation, alignment and cohesion together, weighted, so the
+    /// neighbours only need finding once.
+    fn flock(&self, agents: &[Agent], index: usize) -> PointF {
+        let agent = &agents[index];
+        let mut away = PointF::zero();
+        let mut heading = PointF::zero();
+        let mut centre = PointF::zero();
+        let mut neighbours = 0;
+
+        self.grid
+            .for_each_near(agent.position, self.neighbour_radius, |other_index| {
+                if other_index == index {
+                    return true;
+                }
+                let other = &agents[other_index];
+                let offset = agent.position - other.position;
+                let distance = offset.mag();
+                if distance >= self.neighbour_radius {
+                    return true;
+                }
+                if distance < self.separation_radius && distance > f32::EPSILON {
+                    // pushed harder the closer they are
+                    away += offset.normalized() / distance;
+                }
+                heading += other.velocity;
+                centre += other.position;
+                neighbours += 1;
+                neighbours < self.max_neighbours
+            });
+        if neighbours == 0 {
+            return PointF::zero();
+        }
+
+        let w = self.weights;
+        let count = neighbours as f32;
+        let mut force = PointF::zero();
+        if away.mag_sq() > 0.0 {
+            force += self.steer(agent, away.normalized() * self.max_speed) * w.separation;
+        }
+        if heading.mag_sq() > 0.0 {
+            force +=
+                self.steer(agent, (heading / count).normalized() * self.max_speed) * w.alignment;
+        }
+        force += self.seek(agent, centre / count) * w.cohesion;
+        force
+    }
+
+    /// Turns agents back once they come within `margin` of the bounds.
+    pub fn containment(&self, agent: &Agent) -> PointF {
+        let b = &self.bounds;
+        let p = agent.position;
+        let mut inward = PointF::zero();
+        if p.x < b.x1 as f32 + self.margin {
+            inward.x += 1.0;
+        } else if p.x > b.x2 as f32 - self.margin {
+            inward.x -= 1.0;
+        }
+        if p.y < b.y1 as f32 + self.margin {
+            inward.y += 1.0;
+        } else if p.y > b.y2 as f32 - self.margin {
+            inward.y -= 1.0;
+        }
+        if inward.mag_sq() == 0.0 {
+            return PointF::zero();
+        }
+        self.steer(agent, inward.normalized() * self.max_speed)
+    }
+}
+
+fn limit(vector: PointF, max: f32) -> PointF {
+    let length = vector.mag();
+    if length > max {
+        vector * (max / length)
+    } else {
+        vector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steering() -> Steering {
+        Steering::new(Rect::with_exact(0, 0, 400, 300))
+    }
+
+    fn resting_at(x: f32, y: f32) -> Agent {
+        Agent {
+            position: PointF::new(x, y),
+            velocity: PointF::zero(),
+            wander_angle: 0.0,
+        }
+    }
+
+    #[test]
+    fn seeks_towards_the_target() {
+        let steering = steering();
+        let agent = resting_at(200.0, 150.0);
+
+        let force = steering.seek(&agent, PointF::new(260.0, 150.0));
+        assert!(force.x > 0.0 && force.y.abs() < 1e-4, "{:?}", force);
+        let force = steering.seek(&agent, PointF::new(200.0, 100.0));
+        assert!(force.y < 0.0 && force.x.abs() < 1e-4, "{:?}", force);
+        assert_eq!(steering.seek(&agent, agent.position), PointF::zero());
+    }
+
+    #[test]
+    fn flees_harder_the_closer_the_threat() {
+        let steering = steering();
+        let agent = resting_at(200.0, 150.0);
+
+        let near = steering.flee(&agent, PointF::new(210.0, 150.0));
+        let far = steering.flee(&agent, PointF::new(260.0, 150.0));
+        assert!(near.x < 0.0 && near.y.abs() < 1e-4, "{:?}", near);
+        assert!(far.x < 0.0 && far.x > near.x, "{:?} {:?}", far, near);
+    }
+
+    #[test]
+    fn ignores_threats_past_the_panic_radius() {
+        let steering = steering();
+        let agent = resting_at(200.0, 150.0);
+        let edge = PointF::new(200.0 + steering.panic_radius, 150.0);
+        assert_eq!(steering.flee(&agent, edge), PointF::zero());
+        let inside = PointF::new(200.0 + steering.panic_radius - 1.0, 150.0);
+        assert_ne!(steering.flee(&agent, inside), PointF::zero());
+    }
+
+    #[test]
+    fn containment_turns_agents_back_from_the_edges() {
+        let steering = steering();
+
+        let force = steering.containment(&resting_at(10.0, 150.0));
+        assert!(force.x > 0.0 && force.y == 0.0, "{:?}", force);
+        let force = steering.containment(&resting_at(395.0, 290.0));
+        assert!(force.x < 0.0 && force.y < 0.0, "{:?}", force);
+        assert_eq!(
+            steering.containment(&resting_at(200.0, 150.0)),
+            PointF::zero()
+        );
+    }
+}
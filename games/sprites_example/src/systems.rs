@@ -0,0 +1,193 @@
+//! Everything that happens to the doods each frame. [`update`] runs
+//! the simulation and [`render`] lays out what to draw, each as its own
+//! schedule so they can be timed apart.
+
+use bevy_ecs::prelude::*;
+use bracket_lib::prelude::PointF;
+use snake_common::layers::Layer;
+
+use crate::components::*;
+use crate::map::TileMap;
+use crate::resources::*;
+use crate::steering::{Agent, Steering};
+
+/// Chance per move that a dood stumbles, out of 1000.
+const STUMBLE_CHANCE: i32 = 2;
+/// How often a dood gets the chance to stumble.
+const STUMBLE_EVERY_MS: f32 = 66.0;
+
+pub fn update() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems((steer, follow_path, drag, movement, animate, stumble).chain());
+    schedule
+}
+
+pub fn render() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(draw);
+    schedule
+}
+
+/// Turns every entity's velocity towards what its behaviours want.
+/// Steering needs to see all of them at once, for flocking.
+fn steer(
+    mut steering: ResMut<Steering>,
+    mut rng: ResMut<Rng>,
+    time: Res<FrameTime>,
+    target: Res<Target>,
+    mut agents: Local<Vec<Agent>>,
+    mut query: Query<(&Position, &mut Velocity, &mut Wander)>,
+) {
+    agents.clear();
+    agents.extend(query.iter().map(|(position, velocity, wander)| Agent {
+        position: position.0,
+        velocity: velocity.0,
+        wander_angle: wander.angle,
+    }));
+    steering.accelerate(&mut agents, target.0, &mut rng.0, time.seconds());
+
+    for ((_, mut velocity, mut wander), agent) in query.iter_mut().zip(agents.iter()) {
+        velocity.0 = agent.velocity;
+        wander.angle = agent.wander_angle;
+    }
+}
+
+/// Walks entities along their paths, overriding what steering wanted.
+/// Other entities still steer around them.
+fn follow_path(
+    mut commands: Commands,
+    steering: Res<Steering>,
+    map: Res<TileMap>,
+    mut query: Query<(Entity, &Position, &mut Velocity, &mut Path), Without<Dragged>>,
+) {
+    // close enough to a waypoint to head for the next one
+    let reach = map.tile_size / 4.0;
+    for (entity, position, mut velocity, mut path) in &mut query {
+        let Some(&waypoint) = path.waypoints.get(path.next) else {
+            velocity.0 = PointF::zero();
+            commands.entity(entity).remove::<Path>();
+            continue;
+        };
+        let offset = waypoint - position.0;
+        let distance = offset.mag();
+        if distance < reach {
+            path.next += 1;
+            continue;
+        }
+        // slow down coming up to the end rather than overshooting it
+        let last = path.next + 1 == path.waypoints.len();
+        let speed = if last {
+            steering.max_speed.min(distance * 3.0)
+        } else {
+            steering.max_speed
+        };
+        velocity.0 = offset / distance * speed;
+    }
+}
+
+/// Keeps dragged entities under the pointer, standing still.
+fn drag(
+    steering: Res<Steering>,
+    cursor: Res<Cursor>,
+    mut query: Query<(&mut Position, &mut Velocity, &Dragged)>,
+) {
+    for (mut position, mut velocity, dragged) in &mut query {
+        position.0 = steering.contain(cursor.0 + dragged.offset);
+        velocity.0 = PointF::zero();
+    }
+}
+
+/// Moves entities by their velocity, sliding along blocked tiles rather
+/// than walking into them.
+fn movement(
+    steering: Res<Steering>,
+    map: Res<TileMap>,
+    time: Res<FrameTime>,
+    mut query: Query<(&mut Position, &mut Velocity), Without<Dragged>>,
+) {
+    for (mut position, mut velocity) in &mut query {
+        let from = position.0;
+        let mut to = steering.contain(from + velocity.0 * time.seconds());
+        // anything dropped on a blocked tile may walk off it
+        if !map.is_blocked_at(from) {
+            if map.is_blocked_at(PointF::new(to.x, from.y)) {
+                to.x = from.x;
+                velocity.0.x = 0.0;
+            }
+            if map.is_blocked_at(to) {
+                to.y = from.y;
+                velocity.0.y = 0.0;
+            }
+        }
+        position.0 = to;
+    }
+}
+
+fn animate(
+    library: Res<Library>,
+    steering: Res<Steering>,
+    time: Res<FrameTime>,
+    mut query: Query<(&Velocity, &mut Animation, &mut Sprite)>,
+) {
+    for (velocity, mut animation, mut sprite) in &mut query {
+        // legs keep up with how fast the entity is actually going
+        let speed = velocity.0.mag() / steering.max_speed;
+        let pace = animation.pace;
+        let animator = &mut animation.animator;
+        animator.speed = pace * (0.3 + speed);
+        animator.update(time.ms);
+        if animator.is_finished(&library.clips) {
+            animator.play(library.walk);
+        }
+        sprite.index = animator.sprite(&library.clips);
+        // keep facing the same way when standing still
+        if velocity.0.x.abs() > 1.0 {
+            sprite.flip = velocity.0.x < 0.0;
+        }
+    }
+}
+
+fn stumble(
+    library: Res<Library>,
+    mut rng: ResMut<Rng>,
+    time: Res<FrameTime>,
+    mut since_ms: Local<f32>,
+    mut query: Query<&mut Animation, With<Dood>>,
+) {
+    *since_ms += time.ms;
+    if *since_ms <= STUMBLE_EVERY_MS {
+        return;
+    }
+    *since_ms = 0.0;
+
+    for mut animation in &mut query {
+        if rng.0.range(0, 1000) < STUMBLE_CHANCE {
+            animation.animator.play(library.stumble);
+        }
+    }
+}
+
+/// Lays out every sprite with its pivot on its entity's position.
+fn draw(
+    sprites: Res<Sprites>,
+    mut list: ResMut<DrawList>,
+    query: Query<(Entity, &Position, &Sprite)>,
+) {
+    list.0.clear();
+    list.0
+        .extend(query.iter().map(|(entity, position, sprite)| {
+            let mut frame = *sprites.index.frame(sprite.index);
+            if sprite.flip {
+                frame = frame.mirrored();
+            }
+            let rect = frame.place(position.0, sprites.scale);
+            Draw {
+                entity,
+                rect,
+                z_order: Layer::Entities.y_sorted(rect, sprite.rank),
+                tint: sprite.tint,
+                sprite: sprite.index,
+                flip: sprite.flip,
+            }
+        }));
+}
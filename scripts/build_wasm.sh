@@ -0,0 +1,82 @@
+#!/usr/bin/env bash
+# Builds every game for the browser into a static bundle under dist/,
+# one directory per game plus an index page linking them.
+#
+#   rustup target add wasm32-unknown-unknown
+#   cargo install wasm-bindgen-cli --version <the wasm-bindgen version in Cargo.lock>
+#   scripts/build_wasm.sh [game...]
+#
+# Serve dist/ with any static file server, e.g. `python3 -m http.server -d dist`.
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+
+GAMES=("$@")
+if [ ${#GAMES[@]} -eq 0 ]; then
+    GAMES=(ascii_snake snake sprites_example)
+fi
+
+TARGET=wasm32-unknown-unknown
+DIST=dist
+
+lock_version=$(grep -A1 '^name = "wasm-bindgen"$' Cargo.lock 2>/dev/null | sed -n 's/^version = "\(.*\)"$/\1/p' || true)
+cli_version=$(wasm-bindgen --version 2>/dev/null | cut -d' ' -f2 || true)
+if [ -z "$cli_version" ]; then
+    echo "wasm-bindgen not found; install it with: cargo install wasm-bindgen-cli --version ${lock_version:-<version>}" >&2
+    exit 1
+fi
+if [ -n "$lock_version" ] && [ "$lock_version" != "$cli_version" ]; then
+    echo "wasm-bindgen $cli_version does not match $lock_version in Cargo.lock" >&2
+    exit 1
+fi
+
+rm -rf "$DIST"
+mkdir -p "$DIST"
+
+links=""
+for game in "${GAMES[@]}"; do
+    echo "building $game"
+    cargo build --release --target "$TARGET" -p "$game"
+    wasm-bindgen "target/$TARGET/release/$game.wasm" \
+        --out-dir "$DIST/$game" --target web --no-typescript
+
+    cat > "$DIST/$game/index.html" <<EOF
+<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>$game</title>
+    <style>
+      body { margin: 0; background: #000; display: flex; justify-content: center; }
+      canvas { margin-top: 1em; }
+    </style>
+  </head>
+  <body>
+    <canvas id="canvas" tabindex="0"></canvas>
+    <script type="module">
+      import init from "./$game.js";
+      document.getElementById("canvas").focus();
+      init();
+    </script>
+  </body>
+</html>
+EOF
+    links="$links      <li><a href=\"$game/\">$game</a></li>
+"
+done
+
+cat > "$DIST/index.html" <<EOF
+<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>Games</title>
+  </head>
+  <body>
+    <ul>
+$links    </ul>
+  </body>
+</html>
+EOF
+
+echo "bundle written to $DIST/"